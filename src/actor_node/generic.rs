@@ -1,6 +1,12 @@
 use std::future::IntoFuture;
 
-use crate::time::TimeDelta;
+use crate::{
+    bridge::TickMessages,
+    params::Params,
+    time::{SimClock, TimeDelta},
+    utils::ActorExt,
+    worker_pool::WorkerPool,
+};
 
 use super::{
     other::{OtherPub, OtherSub},
@@ -10,19 +16,25 @@ use super::{
     vehicle::{VehiclePub, VehicleSub},
 };
 use anyhow::Result;
-use carla::client::{Actor, ActorKind};
+use carla::client::{Actor, ActorBase, ActorKind};
 use futures::{future::BoxFuture, FutureExt};
 use r2r::{builtin_interfaces::msg::Time, Node};
 
-pub fn new(node: &mut Node, actor: Actor) -> Result<(ActorPub, ActorSub)> {
+pub fn new(
+    node: &mut Node,
+    actor: Actor,
+    params: &Params,
+    sim_clock: &SimClock,
+    worker_pool: &WorkerPool,
+) -> Result<(ActorPub, ActorSub)> {
     use ActorKind as K;
     let (pub_, sub) = match actor.into_kinds() {
         K::Vehicle(actor) => {
-            let (pub_, sub) = super::vehicle::new(node, actor)?;
+            let (pub_, sub) = super::vehicle::new(node, actor, params)?;
             (pub_.into(), sub.into())
         }
         K::Sensor(actor) => {
-            let (pub_, sub) = super::sensor::new(node, actor)?;
+            let (pub_, sub) = super::sensor::new(node, actor, params, sim_clock, worker_pool)?;
             (pub_.into(), sub.into())
         }
         K::TrafficLight(actor) => {
@@ -42,6 +54,24 @@ pub fn new(node: &mut Node, actor: Actor) -> Result<(ActorPub, ActorSub)> {
     Ok((pub_, sub))
 }
 
+/// Returns the frame ID of an actor, which is the same as its topic
+/// namespace without the `/carla` prefix.
+pub fn frame_id(actor: Actor) -> Option<String> {
+    use ActorKind as K;
+    let actor_id = actor.id();
+    let frame_id = match actor.into_kinds() {
+        K::Vehicle(actor) => {
+            let role_name: String = actor.parse_attribute("role_name")?;
+            format!("vehicle/{role_name}")
+        }
+        K::Sensor(_) => format!("sensor/id_{actor_id}"),
+        K::TrafficLight(_) => format!("traffic_light/id_{actor_id}"),
+        K::TrafficSign(_) => format!("traffic_sign/id_{actor_id}"),
+        K::Other(_) => format!("other/id_{actor_id}"),
+    };
+    Some(frame_id)
+}
+
 pub enum ActorPub {
     Vehicle(VehiclePub),
     Sensor(SensorPub),
@@ -81,13 +111,18 @@ impl From<VehiclePub> for ActorPub {
 }
 
 impl ActorPub {
-    pub fn poll(&mut self, time: &Time, time_delta: TimeDelta) -> Result<()> {
+    pub fn poll(
+        &mut self,
+        time: &Time,
+        time_delta: TimeDelta,
+        tick_messages: &mut TickMessages,
+    ) -> Result<()> {
         match self {
-            ActorPub::Vehicle(pub_) => pub_.poll(time, time_delta)?,
-            ActorPub::Sensor(pub_) => pub_.poll(time)?,
-            ActorPub::TrafficSign(pub_) => pub_.poll(time)?,
-            ActorPub::Other(pub_) => pub_.poll(time)?,
-            ActorPub::TrafficLight(pub_) => pub_.poll(time)?,
+            ActorPub::Vehicle(pub_) => pub_.poll(time, time_delta, tick_messages)?,
+            ActorPub::Sensor(pub_) => pub_.poll(time, tick_messages)?,
+            ActorPub::TrafficSign(pub_) => pub_.poll(time, tick_messages)?,
+            ActorPub::Other(pub_) => pub_.poll(time, tick_messages)?,
+            ActorPub::TrafficLight(pub_) => pub_.poll(time, tick_messages)?,
         }
 
         Ok(())
@@ -110,7 +145,7 @@ impl IntoFuture for ActorSub {
         async move {
             match self {
                 ActorSub::Vehicle(sub) => sub.await,
-                ActorSub::Sensor(_) => (),
+                ActorSub::Sensor(sub) => sub.await,
                 ActorSub::TrafficSign(_) => (),
                 ActorSub::Other(_) => (),
                 ActorSub::TrafficTraffic(_) => (),
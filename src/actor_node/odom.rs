@@ -1,12 +1,16 @@
 use crate::{
+    bridge::TickMessages,
+    noise::Noise,
     qos,
-    utils::{ActorExt, ActorPhysics},
+    utils::{ActorExt, ActorPhysics, ToRosType},
 };
 use anyhow::Result;
 use carla::client::ActorBase;
 use r2r::{
-    builtin_interfaces::msg::Time, geometry_msgs::msg::AccelWithCovarianceStamped,
-    nav_msgs::msg::Odometry, Node, Publisher,
+    builtin_interfaces::msg::Time,
+    geometry_msgs::msg::{AccelWithCovarianceStamped, TransformStamped},
+    nav_msgs::msg::Odometry,
+    Node, Publisher,
 };
 
 pub struct OdomPub<T>
@@ -14,31 +18,80 @@ where
     T: ActorBase,
 {
     actor: T,
+    frame_id: String,
     accel: Publisher<AccelWithCovarianceStamped>,
     odom: Publisher<Odometry>,
+    broadcast_tf: bool,
+    noise: Option<Noise>,
 }
 
 impl<T> OdomPub<T>
 where
     T: ActorBase,
 {
-    pub fn new(node: &mut Node, actor: T, prefix: &str) -> Result<Self> {
+    /// Creates the odometry publishers of an actor.
+    ///
+    /// The actor frame is named after the `prefix`. If `broadcast_tf`
+    /// is set, the transform from the map to the actor frame is
+    /// broadcast on `/tf` as well.
+    pub fn new(node: &mut Node, actor: T, prefix: &str, broadcast_tf: bool) -> Result<Self> {
         Ok(OdomPub {
             actor,
+            frame_id: prefix.to_string(),
             accel: node.create_publisher(&format!("{prefix}/acceleration"), qos::best_effort())?,
             odom: node.create_publisher(&format!("{prefix}/odometry"), qos::best_effort())?,
+            broadcast_tf,
+            noise: None,
         })
     }
 
-    pub fn poll(&mut self, time: &Time) -> Result<ActorPhysics> {
-        let physics = self.actor.create_physics_msg(time.clone());
+    /// Injects noise into published odometry messages.
+    pub fn with_noise(self, noise: Noise) -> Self {
+        Self {
+            noise: Some(noise),
+            ..self
+        }
+    }
+
+    /// Publishes the odometry and acceleration of the actor.
+    ///
+    /// Only publish failures are returned, so that callers can
+    /// tolerate them.
+    pub fn poll(
+        &mut self,
+        time: &Time,
+        tick_messages: &mut TickMessages,
+    ) -> Result<ActorPhysics, r2r::Error> {
+        let physics = self.actor.create_physics_msg(time.clone(), &self.frame_id);
         let ActorPhysics {
+            transform,
             odom_msg,
             accel_msg,
             ..
         } = &physics;
-        self.odom.publish(odom_msg)?;
         self.accel.publish(accel_msg)?;
+
+        match &mut self.noise {
+            Some(noise) => {
+                if !noise.drop_message() {
+                    let mut odom_msg = odom_msg.clone();
+                    noise.odometry(&mut odom_msg);
+                    self.odom.publish(&odom_msg)?;
+                }
+            }
+            None => self.odom.publish(odom_msg)?,
+        }
+
+        // The transform is broadcast along with the ones of other
+        // actors in the tick.
+        if self.broadcast_tf {
+            tick_messages.transforms.push(TransformStamped {
+                header: odom_msg.header.clone(),
+                child_frame_id: self.frame_id.clone(),
+                transform: transform.to_ros_type(),
+            });
+        }
+
         Ok(physics)
     }
 }
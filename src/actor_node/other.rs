@@ -1,11 +1,12 @@
 use super::odom::OdomPub;
+use crate::bridge::TickMessages;
 use anyhow::Result;
 use carla::client::{Actor, ActorBase};
 use r2r::{builtin_interfaces::msg::Time, Node};
 
 pub fn new(node: &mut Node, actor: Actor) -> Result<(OtherPub, OtherSub)> {
     let actor_id = actor.id();
-    let odom_pub = OdomPub::new(node, actor, &format!("other/id_{actor_id}"))?;
+    let odom_pub = OdomPub::new(node, actor, &format!("other/id_{actor_id}"), true)?;
     let pub_ = OtherPub { odom_pub };
     let sub = OtherSub {};
     Ok((pub_, sub))
@@ -16,8 +17,8 @@ pub struct OtherPub {
 }
 
 impl OtherPub {
-    pub fn poll(&mut self, time: &Time) -> Result<()> {
-        self.odom_pub.poll(time)?;
+    pub fn poll(&mut self, time: &Time, tick_messages: &mut TickMessages) -> Result<()> {
+        self.odom_pub.poll(time, tick_messages)?;
         Ok(())
     }
 }
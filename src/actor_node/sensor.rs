@@ -1,146 +1,1077 @@
 use super::odom::OdomPub;
 use crate::{
+    bridge::TickMessages,
+    coord,
+    lidar_sweep::{Sweep, SweepBuffer, SweepSlice},
+    noise::Noise,
+    params::{DecimationParams, NoiseParams, Params},
     qos,
-    types::{PointFieldType, SensorType},
-    utils::{identity_matrix, ToRosType},
+    time::SimClock,
+    types::{
+        DiagnosticLevel, LidarPointLayout, LidarReturnType, NavSatCovarianceType,
+        NavSatServiceType, NavSatStatusType, PointFieldType, SemanticTag, SensorDropPolicy,
+        SensorType,
+    },
+    utils::{diagonal_matrix, identity_matrix, ActorExt, ToRosType},
+    worker_pool::WorkerPool,
 };
 use anyhow::Result;
 use carla::{
-    client::{ActorBase, Sensor},
+    client::{Actor, ActorBase, Sensor},
     geom::Location,
     sensor::data::{
-        CollisionEvent, Color, Image as CarlaImage, ImuMeasurement, LidarDetection,
-        LidarMeasurement, SemanticLidarDetection, SemanticLidarMeasurement,
+        CollisionEvent, Color, GnssMeasurement, Image as CarlaImage, ImuMeasurement,
+        LaneInvasionEvent, LidarDetection, LidarMeasurement, ObstacleDetectionEvent,
+        RadarDetection, RadarMeasurement, SemanticLidarDetection, SemanticLidarMeasurement,
     },
+    sensor::{SensorData, SensorDataBase},
+};
+use flume::TrySendError;
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt,
 };
-use nalgebra::UnitQuaternion;
+use num_traits::FromPrimitive;
 use r2r::{
     builtin_interfaces::msg::Time,
-    log_warn,
-    sensor_msgs::msg::{Image as RosImage, Imu, PointCloud2, PointField},
-    std_msgs::msg::{Header, String as RosString},
-    Clock, ClockType, Node, Publisher,
+    carla_autoware_bridge_plus_msgs::msg::{
+        CollisionEvent as CollisionEventMsg, ObstacleDetectionEvent as ObstacleDetectionEventMsg,
+        SensorDescription,
+    },
+    carla_msgs::msg::CarlaLaneInvasionEvent,
+    diagnostic_msgs::msg::{DiagnosticStatus, KeyValue},
+    geometry_msgs::msg::{Quaternion, Transform, TransformStamped, Vector3},
+    log_error, log_warn,
+    radar_msgs::msg::{RadarReturn, RadarScan},
+    sensor_msgs::msg::{
+        CameraInfo, Image as RosImage, Imu, NavSatFix, NavSatStatus, PointCloud2, PointField,
+        RegionOfInterest,
+    },
+    std_msgs::msg::{Header, UInt64},
+    vision_msgs::msg::{LabelInfo, VisionClass},
+    Node, Publisher,
 };
-use std::mem;
+use std::{
+    borrow::Cow,
+    f32::consts::{PI, TAU},
+    fmt,
+    future::IntoFuture,
+    mem,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+use tokio::task::JoinError;
+
+/// The far plane distance of depth cameras in meters.
+const MAX_DEPTH: f64 = 1000.0;
 
-pub fn new(node: &mut Node, actor: Sensor) -> Result<(SensorPub, SensorSub)> {
+/// The equatorial Earth radius in meters used by Carla to compute
+/// GNSS coordinates.
+const EARTH_RADIUS: f64 = 6_378_137.0;
+
+/// Carla's default lens distortion attributes of cameras.
+const DEFAULT_LENS_K: f64 = -1.0;
+const DEFAULT_LENS_KCUBE: f64 = 0.0;
+const DEFAULT_LENS_SIZE: f64 = 0.08;
+
+/// The orientation variance of IMU readings in rad². The orientation
+/// is exact, but an all-zero covariance means unknown in
+/// `sensor_msgs/Imu`.
+const IMU_ORIENTATION_VARIANCE: f64 = 1e-9;
+
+/// The minimum interval between error logs of a sensor.
+const ERROR_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn new(
+    node: &mut Node,
+    actor: Sensor,
+    params: &Params,
+    sim_clock: &SimClock,
+    worker_pool: &WorkerPool,
+) -> Result<(SensorPub, SensorSub)> {
     let actor_id = actor.id();
     let type_id = actor.type_id();
     let type_: Option<SensorType> = type_id.parse().ok();
     let prefix = format!("sensor/id_{actor_id}");
+    let parent = actor.parent();
+    let role_name: String = actor.parse_attribute("role_name").unwrap_or_default();
+    let decimation = params.sensor_decimation(&type_id, &role_name);
+    let image_scale = decimation.image_scale.unwrap_or(1) as usize;
 
-    if let Some(type_) = type_ {
-        use SensorType as T;
+    // Lidar slices are decimated after they are assembled into full
+    // sweeps. The other sensors are decimated before enqueuing.
+    let is_lidar = matches!(
+        type_,
+        Some(SensorType::LidarRayCast | SensorType::LidarRayCastSemantic)
+    );
+    let decimate_sweeps = is_lidar && params.lidar_full_sweep;
+
+    // Cameras publish images in the optical frame, which has z
+    // forward, x right and y down.
+    let is_camera = type_.map(|type_| type_.is_camera()).unwrap_or(false);
+    let frame_id = if is_camera {
+        format!("{prefix}/optical")
+    } else {
+        prefix.clone()
+    };
+
+    // Sensors attached to a parent with a known frame are mounted
+    // relative to it. The others follow their own transform broadcast
+    // from the map on every tick.
+    let parent_frame_id = parent.clone().and_then(super::generic::frame_id);
 
-        let mut clock = Clock::create(ClockType::RosTime)?;
+    // Segmentation cameras publish the latched label mapping along
+    // with the description.
+    let mut label_info_pub = None;
+
+    let handler = if let Some(type_) = type_ {
+        use SensorType as T;
 
-        let mut next_header = move || {
-            let time = clock.get_now()?;
-            let time = Clock::to_builtin_time(&time);
+        // Stamp messages with the simulation time of the sensor data
+        // rather than the time the data arrives.
+        let sim_clock = sim_clock.clone();
+        let frame_id = frame_id.clone();
+        let next_header = move |timestamp: f64| {
+            let time = sim_clock.stamp(timestamp).ok_or(SensorError::Stamp)?;
             let header = Header {
                 stamp: time,
-                frame_id: "".to_string(),
+                frame_id: frame_id.clone(),
             };
-            anyhow::Ok(header)
+            Ok(header)
         };
 
-        match type_ {
+        let handler: SensorHandler = match type_ {
             T::CameraRgb => {
                 let mut pub_ =
                     node.create_publisher(&format!("{prefix}/image"), qos::best_effort())?;
+                let mut info_pub =
+                    node.create_publisher(&format!("{prefix}/camera_info"), qos::best_effort())?;
+                let camera_info = camera_info_msg(&actor, image_scale);
+
+                let mut noise = Noise::new(&params.noise, actor_id);
+                let mut buffer = vec![];
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    if noise.drop_message() {
+                        return Ok(());
+                    }
+                    let header = next_header(data.timestamp())?;
+                    camera_info_callback(header.clone(), &camera_info, &mut info_pub)?;
+                    let image: CarlaImage = convert(data)?;
+                    camera_callback(
+                        header,
+                        CameraFrame::new(&image, image_scale),
+                        &mut noise,
+                        &mut buffer,
+                        &mut pub_,
+                    )?;
+                    Ok(())
+                })
+            }
+            T::CameraDepth => {
+                let mut depth_pub =
+                    node.create_publisher(&format!("{prefix}/depth"), qos::best_effort())?;
+                let mut log_depth_pub =
+                    node.create_publisher(&format!("{prefix}/log_depth"), qos::best_effort())?;
+                let mut info_pub =
+                    node.create_publisher(&format!("{prefix}/camera_info"), qos::best_effort())?;
+                let camera_info = camera_info_msg(&actor, image_scale);
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    camera_info_callback(header.clone(), &camera_info, &mut info_pub)?;
+                    let image: CarlaImage = convert(data)?;
+                    depth_camera_callback(
+                        header,
+                        CameraFrame::new(&image, image_scale),
+                        &mut depth_pub,
+                        &mut log_depth_pub,
+                    )?;
+                    Ok(())
+                })
+            }
+            T::CameraSemanticSegmentation => {
+                let mut label_pub =
+                    node.create_publisher(&format!("{prefix}/label"), qos::best_effort())?;
+                let mut color_pub =
+                    node.create_publisher(&format!("{prefix}/image"), qos::best_effort())?;
+                label_info_pub =
+                    Some(node.create_publisher(&format!("{prefix}/label_info"), qos::latched())?);
+                let mut info_pub =
+                    node.create_publisher(&format!("{prefix}/camera_info"), qos::best_effort())?;
+                let camera_info = camera_info_msg(&actor, image_scale);
 
-                actor.listen(move |data| {
-                    let header = next_header().unwrap();
-                    camera_callback(header, data.try_into().unwrap(), &mut pub_);
-                });
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    camera_info_callback(header.clone(), &camera_info, &mut info_pub)?;
+                    let image: CarlaImage = convert(data)?;
+                    semantic_camera_callback(
+                        header,
+                        CameraFrame::new(&image, image_scale),
+                        &mut label_pub,
+                        &mut color_pub,
+                    )?;
+                    Ok(())
+                })
+            }
+            T::CameraInstanceSegmentation => {
+                let mut label_pub =
+                    node.create_publisher(&format!("{prefix}/label"), qos::best_effort())?;
+                let mut instance_pub =
+                    node.create_publisher(&format!("{prefix}/instance"), qos::best_effort())?;
+                label_info_pub =
+                    Some(node.create_publisher(&format!("{prefix}/label_info"), qos::latched())?);
+                let mut info_pub =
+                    node.create_publisher(&format!("{prefix}/camera_info"), qos::best_effort())?;
+                let camera_info = camera_info_msg(&actor, image_scale);
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    camera_info_callback(header.clone(), &camera_info, &mut info_pub)?;
+                    let image: CarlaImage = convert(data)?;
+                    instance_camera_callback(
+                        header,
+                        CameraFrame::new(&image, image_scale),
+                        &mut label_pub,
+                        &mut instance_pub,
+                    )?;
+                    Ok(())
+                })
             }
             T::LidarRayCast => {
                 let mut pub_ =
                     node.create_publisher(&format!("{prefix}/pointcloud"), qos::best_effort())?;
+                let config = lidar_config(&actor);
+                let layout = params.lidar_point_layout;
+                let mut noise = Noise::new(&params.noise, actor_id);
+                let mut sweep = params
+                    .lidar_full_sweep
+                    .then(|| SweepBuffer::new(config.rotation_frequency));
+                let mut decimator = Decimator::new(&decimation);
+                let mut buffer = vec![];
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    let measure: LidarMeasurement = convert(data)?;
+                    let timestamp = measure.timestamp();
+                    let slice = noise.lidar(measure.as_slice());
 
-                actor.listen(move |data| {
-                    let header = next_header().unwrap();
-                    lidar_callback(header, data.try_into().unwrap(), &mut pub_);
-                });
+                    match &mut sweep {
+                        Some(sweep) => {
+                            if let Some(sweep) = sweep.push(header, timestamp, &slice) {
+                                if !decimator.accept(timestamp) {
+                                    return Ok(());
+                                }
+                                lidar_callback(
+                                    sweep,
+                                    layout,
+                                    &config,
+                                    &mut noise,
+                                    &mut buffer,
+                                    &mut pub_,
+                                )?;
+                            }
+                        }
+                        None => {
+                            let sweep = Sweep {
+                                header,
+                                timestamp,
+                                slices: vec![SweepSlice {
+                                    end_time: timestamp,
+                                    len: slice.len(),
+                                }],
+                                points: slice,
+                            };
+                            lidar_callback(
+                                sweep,
+                                layout,
+                                &config,
+                                &mut noise,
+                                &mut buffer,
+                                &mut pub_,
+                            )?;
+                        }
+                    }
+                    Ok(())
+                })
             }
             T::LidarRayCastSemantic => {
                 let mut pub_ = node.create_publisher(
                     &format!("{prefix}/semantic_pointcloud"),
                     qos::best_effort(),
                 )?;
+                let rotation_frequency =
+                    actor.parse_attribute("rotation_frequency").unwrap_or(10.0);
+                let mut sweep = params
+                    .lidar_full_sweep
+                    .then(|| SweepBuffer::new(rotation_frequency));
+                let mut decimator = Decimator::new(&decimation);
+                let mut buffer = vec![];
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    let measure: SemanticLidarMeasurement = convert(data)?;
 
-                actor.listen(move |data| {
-                    let header = next_header().unwrap();
-                    semantic_lidar_callback(header, data.try_into().unwrap(), &mut pub_);
-                });
+                    match &mut sweep {
+                        Some(sweep) => {
+                            let timestamp = measure.timestamp();
+                            if let Some(Sweep { header, points, .. }) =
+                                sweep.push(header, timestamp, measure.as_slice())
+                            {
+                                if !decimator.accept(timestamp) {
+                                    return Ok(());
+                                }
+                                semantic_lidar_callback(header, &points, &mut buffer, &mut pub_)?;
+                            }
+                        }
+                        None => semantic_lidar_callback(
+                            header,
+                            measure.as_slice(),
+                            &mut buffer,
+                            &mut pub_,
+                        )?,
+                    }
+                    Ok(())
+                })
+            }
+            T::Radar => {
+                let mut pointcloud_pub =
+                    node.create_publisher(&format!("{prefix}/pointcloud"), qos::best_effort())?;
+                let mut scan_pub =
+                    node.create_publisher(&format!("{prefix}/scan"), qos::best_effort())?;
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    radar_callback(header, convert(data)?, &mut pointcloud_pub, &mut scan_pub)?;
+                    Ok(())
+                })
+            }
+            T::Gnss => {
+                let mut pub_ =
+                    node.create_publisher(&format!("{prefix}/fix"), qos::best_effort())?;
+                let stddev = GnssStddev {
+                    lat: actor.parse_attribute("noise_lat_stddev").unwrap_or(0.0),
+                    lon: actor.parse_attribute("noise_lon_stddev").unwrap_or(0.0),
+                    alt: actor.parse_attribute("noise_alt_stddev").unwrap_or(0.0),
+                };
+
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    gnss_callback(header, convert(data)?, &stddev, &mut pub_)?;
+                    Ok(())
+                })
             }
             T::Imu => {
                 let mut pub_ =
                     node.create_publisher(&format!("{prefix}/imu"), qos::best_effort())?;
+                let covariance = imu_covariance(&actor, &params.noise);
+                let mut noise = Noise::new(&params.noise, actor_id);
 
-                actor.listen(move |data| {
-                    let header = next_header().unwrap();
-                    imu_callback(header, data.try_into().unwrap(), &mut pub_);
-                });
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    if noise.drop_message() {
+                        return Ok(());
+                    }
+                    let header = next_header(data.timestamp())?;
+                    imu_callback(header, convert(data)?, &covariance, &mut noise, &mut pub_)?;
+                    Ok(())
+                })
             }
             T::Collision => {
                 let mut pub_ =
                     node.create_publisher(&format!("{prefix}/event"), qos::best_effort())?;
-                actor.listen(move |data| {
-                    let header = next_header().unwrap();
-                    collision_callback(header, data.try_into().unwrap(), &mut pub_);
-                });
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    collision_callback(header, convert(data)?, &mut pub_)?;
+                    Ok(())
+                })
             }
-        }
+            T::LaneInvasion => {
+                let mut pub_ =
+                    node.create_publisher(&format!("{prefix}/event"), qos::best_effort())?;
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    lane_invasion_callback(header, convert(data)?, &mut pub_)?;
+                    Ok(())
+                })
+            }
+            T::Obstacle => {
+                let mut pub_ =
+                    node.create_publisher(&format!("{prefix}/event"), qos::best_effort())?;
+                Box::new(move |data: SensorData| -> Result<(), SensorError> {
+                    let header = next_header(data.timestamp())?;
+                    obstacle_callback(header, convert(data)?, &mut pub_)?;
+                    Ok(())
+                })
+            }
+        };
+        Some(handler)
     } else {
         log_warn!(
             env!("CARGO_BIN_NAME"),
             "Unsupported sensor type '{}'",
             type_id
         );
-    }
+        None
+    };
 
-    let type_pub = node.create_publisher(&format!("{prefix}/type"), qos::best_effort())?;
-    let odom_pub = OdomPub::new(node, actor, &prefix)?;
+    // The listener only enqueues the data to keep the Carla streaming
+    // thread responsive. The conversion runs in the worker pool.
+    let dropped = Arc::new(AtomicU64::new(0));
+    let errors = Arc::new(ErrorCounts::default());
+    let future = match handler {
+        Some(handler) => {
+            let (tx, rx) = flume::bounded(params.sensor_queue_depth);
+            let drop_policy = params.sensor_drop_policy;
+            let listener_rx = rx.clone();
+            let listener_dropped = dropped.clone();
+            let mut decimator = Decimator::new(&decimation);
+            actor.listen(move |data| {
+                // Skip the data before it is converted.
+                if !decimate_sweeps && !decimator.accept(data.timestamp()) {
+                    return;
+                }
+                enqueue(&tx, &listener_rx, data, drop_policy, &listener_dropped);
+            });
+            run_worker(
+                rx,
+                handler,
+                worker_pool.clone(),
+                errors.clone(),
+                prefix.clone(),
+            )
+            .boxed()
+        }
+        None => future::ready(()).boxed(),
+    };
+
+    let description_pub =
+        node.create_publisher(&format!("{prefix}/description"), qos::latched())?;
+    let dropped_pub = node.create_publisher(&format!("{prefix}/dropped"), qos::best_effort())?;
+    // The transform of mounted sensors is static and broadcast once.
+    let odom_pub = OdomPub::new(node, actor.clone(), &prefix, parent_frame_id.is_none())?;
     let pub_ = SensorPub {
+        actor,
         type_id,
-        type_pub,
+        parent,
+        parent_frame_id,
+        frame_id,
+        is_mounted: false,
+        description_pub,
+        label_info_pub,
         odom_pub,
+        dropped,
+        dropped_pub,
+        errors,
+        log_limiter: LogLimiter::default(),
+        last_diagnostics_sec: None,
+        reported_errors: 0,
+        prefix,
     };
-    let sub = SensorSub {};
+    let sub = SensorSub { future };
     Ok((pub_, sub))
 }
 
+type SensorHandler = Box<dyn FnMut(SensorData) -> Result<(), SensorError> + Send>;
+
+/// Failures in sensor callbacks.
+#[derive(Debug)]
+enum SensorError {
+    /// The simulation clock is not anchored to the ROS time yet.
+    Stamp,
+    /// The sensor data does not have the expected type.
+    Conversion,
+    /// The message cannot be published.
+    Publish(r2r::Error),
+    /// The job in the worker pool panicked or was cancelled.
+    Job(JoinError),
+}
+
+impl From<r2r::Error> for SensorError {
+    fn from(error: r2r::Error) -> Self {
+        Self::Publish(error)
+    }
+}
+
+impl fmt::Display for SensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stamp => write!(f, "the simulation clock is not anchored yet"),
+            Self::Conversion => write!(f, "unexpected sensor data type"),
+            Self::Publish(error) => write!(f, "unable to publish: {error}"),
+            Self::Job(error) => write!(f, "the worker job failed: {error}"),
+        }
+    }
+}
+
+/// The numbers of sensor callback failures per kind.
+#[derive(Debug, Default)]
+struct ErrorCounts {
+    stamp: AtomicU64,
+    conversion: AtomicU64,
+    publish: AtomicU64,
+    job: AtomicU64,
+}
+
+impl ErrorCounts {
+    fn add(&self, error: &SensorError) {
+        let count = match error {
+            SensorError::Stamp => &self.stamp,
+            SensorError::Conversion => &self.conversion,
+            SensorError::Publish(_) => &self.publish,
+            SensorError::Job(_) => &self.job,
+        };
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        [&self.stamp, &self.conversion, &self.publish, &self.job]
+            .into_iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum()
+    }
+}
+
+/// Limits the rate of error logs.
+#[derive(Debug, Default)]
+struct LogLimiter {
+    last_log: Option<Instant>,
+    suppressed: u64,
+}
+
+impl LogLimiter {
+    /// Returns the number of suppressed logs since the previous log if
+    /// logging is allowed now.
+    fn check(&mut self) -> Option<u64> {
+        let now = Instant::now();
+        match self.last_log {
+            Some(last_log) if now.duration_since(last_log) < ERROR_LOG_INTERVAL => {
+                self.suppressed += 1;
+                None
+            }
+            _ => {
+                self.last_log = Some(now);
+                Some(mem::take(&mut self.suppressed))
+            }
+        }
+    }
+}
+
+/// Skips sensor data according to the decimation parameters.
+#[derive(Debug)]
+struct Decimator {
+    every_nth: u32,
+    /// The minimum interval between accepted data in seconds.
+    min_interval: f64,
+    count: u32,
+    last_timestamp: Option<f64>,
+}
+
+impl Decimator {
+    fn new(params: &DecimationParams) -> Self {
+        Self {
+            every_nth: params.every_nth.unwrap_or(1),
+            min_interval: params.max_rate.map(|rate| 1.0 / rate).unwrap_or(0.0),
+            count: 0,
+            last_timestamp: None,
+        }
+    }
+
+    /// Returns true if the data at the simulation timestamp should be
+    /// published.
+    fn accept(&mut self, timestamp: f64) -> bool {
+        let count = self.count;
+        self.count = (count + 1) % self.every_nth;
+        if count != 0 {
+            return false;
+        }
+
+        // Tolerate rounding errors in timestamps. Start over if the
+        // simulation time goes backwards.
+        if let Some(last) = self.last_timestamp {
+            if last <= timestamp && timestamp - last < self.min_interval - 1e-6 {
+                return false;
+            }
+        }
+        self.last_timestamp = Some(timestamp);
+        true
+    }
+}
+
+/// Converts sensor data to the measurement type of the sensor.
+fn convert<T>(data: SensorData) -> Result<T, SensorError>
+where
+    T: TryFrom<SensorData>,
+{
+    data.try_into().map_err(|_| SensorError::Conversion)
+}
+
+/// Enqueues sensor data without blocking. If the queue is full, the
+/// data is dropped according to the drop policy.
+fn enqueue(
+    tx: &flume::Sender<SensorData>,
+    rx: &flume::Receiver<SensorData>,
+    data: SensorData,
+    drop_policy: SensorDropPolicy,
+    dropped: &AtomicU64,
+) {
+    let data = match tx.try_send(data) {
+        Ok(()) => return,
+        Err(TrySendError::Full(data)) => data,
+        Err(TrySendError::Disconnected(_)) => return,
+    };
+
+    match drop_policy {
+        SensorDropPolicy::DropNewest => {
+            dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        SensorDropPolicy::DropOldest => {
+            // Make room for the newest data. The worker may have
+            // taken the oldest one in the meantime, so a drop is
+            // counted only if the data is actually removed here.
+            if rx.try_recv().is_ok() {
+                dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            if let Err(TrySendError::Full(_)) = tx.try_send(data) {
+                dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Converts and publishes sensor data in the worker pool. The data of
+/// a sensor is handled one at a time to keep the order.
+async fn run_worker(
+    rx: flume::Receiver<SensorData>,
+    handler: SensorHandler,
+    worker_pool: WorkerPool,
+    errors: Arc<ErrorCounts>,
+    prefix: String,
+) {
+    let mut log_limiter = LogLimiter::default();
+
+    // The handler is shared with the jobs, so that it outlives a job
+    // that panics.
+    let handler = Arc::new(Mutex::new(handler));
+
+    while let Ok(data) = rx.recv_async().await {
+        let job_handler = handler.clone();
+        let result = worker_pool
+            .run(move || {
+                let mut handler = job_handler.lock().unwrap_or_else(PoisonError::into_inner);
+                handler(data)
+            })
+            .await
+            .unwrap_or_else(|error| Err(SensorError::Job(error)));
+
+        if let Err(error) = result {
+            errors.add(&error);
+
+            if let Some(suppressed) = log_limiter.check() {
+                log_error!(
+                    env!("CARGO_BIN_NAME"),
+                    "Sensor '{}' failed: {} ({} similar errors suppressed)",
+                    prefix,
+                    error,
+                    suppressed
+                );
+            }
+        }
+    }
+}
+
 pub struct SensorPub {
+    actor: Sensor,
     type_id: String,
-    type_pub: Publisher<RosString>,
+    parent: Option<Actor>,
+    /// The frame of the parent. It is unknown if the sensor is not
+    /// attached or the parent has no frame.
+    parent_frame_id: Option<String>,
+    /// The frame of the published data.
+    frame_id: String,
+    /// Whether the static transforms, the description and the label
+    /// mapping are published.
+    is_mounted: bool,
+    description_pub: Publisher<SensorDescription>,
+    label_info_pub: Option<Publisher<LabelInfo>>,
     odom_pub: OdomPub<Sensor>,
+    dropped: Arc<AtomicU64>,
+    dropped_pub: Publisher<UInt64>,
+    errors: Arc<ErrorCounts>,
+    log_limiter: LogLimiter,
+    last_diagnostics_sec: Option<i32>,
+    reported_errors: u64,
+    prefix: String,
 }
 
 impl SensorPub {
-    pub fn poll(&mut self, time: &Time) -> Result<()> {
-        let type_msg = RosString {
-            data: self.type_id.to_string(),
+    pub fn poll(&mut self, time: &Time, tick_messages: &mut TickMessages) -> Result<()> {
+        let dropped_msg = UInt64 {
+            data: self.dropped.load(Ordering::Relaxed),
         };
-        self.type_pub.publish(&type_msg)?;
-        self.odom_pub.poll(time)?;
+        if let Err(error) = self.dropped_pub.publish(&dropped_msg) {
+            self.report_error(SensorError::Publish(error));
+        }
+        if let Err(error) = self.odom_pub.poll(time, tick_messages) {
+            self.report_error(SensorError::Publish(error));
+        }
+
+        // Mount the sensor on the first tick, when the transforms of
+        // the sensor and its parent are up to date.
+        if !self.is_mounted {
+            self.is_mounted = true;
+            self.mount(time, tick_messages);
+        }
+
+        // Report diagnostics once per second of simulation time.
+        if self.last_diagnostics_sec != Some(time.sec) {
+            self.last_diagnostics_sec = Some(time.sec);
+            let status = self.diagnostic_status();
+            tick_messages.diagnostics.push(status);
+        }
+
         Ok(())
     }
+
+    /// Adds the static transforms of the sensor frames and publishes
+    /// the description including the mount and the label mapping.
+    ///
+    /// Carla does not expose the attachment transform, so the mount is
+    /// derived from the transforms of the sensor and its parent in the
+    /// same tick. It stays fixed for rigid attachments.
+    fn mount(&mut self, time: &Time, tick_messages: &mut TickMessages) {
+        let actor_id = self.actor.id();
+        let transform = coord::isometry(&self.actor.transform());
+        let (mount, mount_frame_id) = match (&self.parent, &self.parent_frame_id) {
+            (Some(parent), Some(parent_frame_id)) => (
+                coord::isometry(&parent.transform()).inverse() * transform,
+                parent_frame_id.as_str(),
+            ),
+            _ => (transform, "map"),
+        };
+
+        if let Some(parent_frame_id) = &self.parent_frame_id {
+            tick_messages.static_transforms.push((
+                actor_id,
+                TransformStamped {
+                    header: Header {
+                        stamp: Time::default(),
+                        frame_id: parent_frame_id.clone(),
+                    },
+                    child_frame_id: self.prefix.clone(),
+                    transform: mount.to_ros_type(),
+                },
+            ));
+        }
+
+        // The optical frame of cameras is rotated from the sensor
+        // frame.
+        if self.frame_id != self.prefix {
+            tick_messages.static_transforms.push((
+                actor_id,
+                TransformStamped {
+                    header: Header {
+                        stamp: Time::default(),
+                        frame_id: self.prefix.clone(),
+                    },
+                    child_frame_id: self.frame_id.clone(),
+                    transform: Transform {
+                        translation: Vector3 {
+                            x: 0.0,
+                            y: 0.0,
+                            z: 0.0,
+                        },
+                        rotation: Quaternion {
+                            x: -0.5,
+                            y: 0.5,
+                            z: -0.5,
+                            w: 0.5,
+                        },
+                    },
+                },
+            ));
+        }
+
+        let header = Header {
+            stamp: time.clone(),
+            frame_id: mount_frame_id.to_string(),
+        };
+        let description = description_msg(
+            &self.actor,
+            &self.prefix,
+            self.parent.as_ref(),
+            header,
+            mount.to_ros_type(),
+        );
+        if let Err(error) = self.description_pub.publish(&description) {
+            self.report_error(SensorError::Publish(error));
+        }
+
+        if let Some(label_info_pub) = &self.label_info_pub {
+            if let Err(error) = label_info_pub.publish(&semantic_label_info()) {
+                self.report_error(SensorError::Publish(error));
+            }
+        }
+    }
+
+    /// Counts and logs a failure without stopping the bridge.
+    fn report_error(&mut self, error: SensorError) {
+        self.errors.add(&error);
+
+        if let Some(suppressed) = self.log_limiter.check() {
+            log_error!(
+                env!("CARGO_BIN_NAME"),
+                "Sensor '{}' failed: {} ({} similar errors suppressed)",
+                self.prefix,
+                error,
+                suppressed
+            );
+        }
+    }
+
+    fn diagnostic_status(&mut self) -> DiagnosticStatus {
+        let total = self.errors.total();
+        let new_errors = total - self.reported_errors;
+        self.reported_errors = total;
+
+        let (level, message) = if new_errors == 0 {
+            (DiagnosticLevel::OK, "OK".to_string())
+        } else {
+            (
+                DiagnosticLevel::WARN,
+                format!("{new_errors} errors since the last report"),
+            )
+        };
+
+        let key_value = |key: &str, count: &AtomicU64| KeyValue {
+            key: key.to_string(),
+            value: count.load(Ordering::Relaxed).to_string(),
+        };
+        let values = vec![
+            key_value("stamp_errors", &self.errors.stamp),
+            key_value("conversion_errors", &self.errors.conversion),
+            key_value("publish_errors", &self.errors.publish),
+            key_value("job_errors", &self.errors.job),
+            key_value("dropped", &self.dropped),
+        ];
+
+        DiagnosticStatus {
+            level: level as u8,
+            name: self.prefix.clone(),
+            message,
+            hardware_id: self.type_id.clone(),
+            values,
+        }
+    }
+}
+
+/// Builds the sensor description including the type, the mounting
+/// transform and the blueprint attributes.
+fn description_msg(
+    actor: &Sensor,
+    prefix: &str,
+    parent: Option<&Actor>,
+    header: Header,
+    mount: Transform,
+) -> SensorDescription {
+    let attributes = actor
+        .attributes()
+        .iter()
+        .map(|attr| KeyValue {
+            key: attr.id().to_string(),
+            value: attr.value_string(),
+        })
+        .collect();
+
+    SensorDescription {
+        header,
+        type_id: actor.type_id(),
+        role_name: actor.parse_attribute("role_name").unwrap_or_default(),
+        parent_id: parent.map(|parent| parent.id()).unwrap_or(0),
+        frame_id: prefix.to_string(),
+        mount,
+        attributes,
+    }
+}
+
+pub struct SensorSub {
+    future: BoxFuture<'static, ()>,
+}
+
+impl IntoFuture for SensorSub {
+    type Output = ();
+    type IntoFuture = BoxFuture<'static, ()>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.future
+    }
+}
+
+/// Builds the camera intrinsics from the blueprint attributes for
+/// images downscaled by `scale`.
+fn camera_info_msg(actor: &Sensor, scale: usize) -> CameraInfo {
+    let width: u32 = actor.parse_attribute("image_size_x").unwrap_or(800);
+    let height: u32 = actor.parse_attribute("image_size_y").unwrap_or(600);
+    let width = width.div_ceil(scale as u32);
+    let height = height.div_ceil(scale as u32);
+    let fov: f64 = actor.parse_attribute("fov").unwrap_or(90.0);
+
+    let cx = width as f64 / 2.0;
+    let cy = height as f64 / 2.0;
+    let fx = width as f64 / (2.0 * (fov.to_radians() / 2.0).tan());
+    let fy = fx;
+
+    // Carla's lens effect is a post-process distortion rather than
+    // the plumb bob model. It scales the radius by 1 + k*r^2 +
+    // kcube*r^3, with r measured in screen coordinates shrunk by
+    // `lens_x_size` and `lens_y_size`. The plumb bob model has no r^3
+    // term and measures r on the normalized image plane, so `lens_k`
+    // as k1 only roughly approximates the effect. The other lens
+    // attributes cannot be expressed and are warned about when they
+    // leave their defaults.
+    let lens_circle_multiplier: f64 = actor
+        .parse_attribute("lens_circle_multiplier")
+        .unwrap_or(0.0);
+    let k1 = if lens_circle_multiplier > 0.0 {
+        let ignored: Vec<_> = [
+            ("lens_kcube", DEFAULT_LENS_KCUBE),
+            ("lens_x_size", DEFAULT_LENS_SIZE),
+            ("lens_y_size", DEFAULT_LENS_SIZE),
+        ]
+        .into_iter()
+        .filter(|&(id, default)| {
+            actor
+                .parse_attribute::<f64>(id)
+                .is_some_and(|value| value != default)
+        })
+        .map(|(id, _)| id)
+        .collect();
+        if !ignored.is_empty() {
+            log_warn!(
+                env!("CARGO_BIN_NAME"),
+                "The camera info of sensor {} ignores non-default {}",
+                actor.id(),
+                ignored.join(", ")
+            );
+        }
+
+        actor.parse_attribute("lens_k").unwrap_or(DEFAULT_LENS_K)
+    } else {
+        0.0
+    };
+
+    #[rustfmt::skip]
+    let k = vec![
+        fx, 0.0, cx,
+        0.0, fy, cy,
+        0.0, 0.0, 1.0,
+    ];
+    #[rustfmt::skip]
+    let p = vec![
+        fx, 0.0, cx, 0.0,
+        0.0, fy, cy, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+    ];
+
+    CameraInfo {
+        header: Header::default(),
+        height,
+        width,
+        distortion_model: "plumb_bob".to_string(),
+        d: vec![k1, 0.0, 0.0, 0.0, 0.0],
+        k,
+        r: identity_matrix(3).into_raw_vec(),
+        p,
+        binning_x: 0,
+        binning_y: 0,
+        roi: RegionOfInterest::default(),
+    }
+}
+
+/// A camera image, which is optionally downscaled.
+struct CameraFrame<'a> {
+    pixels: Cow<'a, [Color]>,
+    width: usize,
+    height: usize,
 }
 
-pub struct SensorSub {}
+impl<'a> CameraFrame<'a> {
+    /// Downscales the image by keeping every `scale`th pixel in both
+    /// directions. The pixels are borrowed if the scale is 1.
+    fn new(image: &'a CarlaImage, scale: usize) -> Self {
+        let slice = image.as_slice();
+        let width = image.width();
+        let height = image.height();
+
+        if scale <= 1 || slice.is_empty() {
+            return Self {
+                pixels: Cow::Borrowed(slice),
+                width,
+                height,
+            };
+        }
+
+        let pixels: Vec<_> = slice
+            .chunks_exact(width)
+            .step_by(scale)
+            .flat_map(|row| row.iter().step_by(scale).cloned())
+            .collect();
+
+        Self {
+            pixels: Cow::Owned(pixels),
+            width: width.div_ceil(scale),
+            height: height.div_ceil(scale),
+        }
+    }
 
-fn camera_callback(header: Header, image: CarlaImage, pub_: &mut Publisher<RosImage>) {
+    fn as_slice(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+fn camera_info_callback(
+    header: Header,
+    camera_info: &CameraInfo,
+    pub_: &mut Publisher<CameraInfo>,
+) -> Result<(), SensorError> {
+    let msg = CameraInfo {
+        header,
+        ..camera_info.clone()
+    };
+    pub_.publish(&msg)?;
+    Ok(())
+}
+
+fn camera_callback(
+    header: Header,
+    image: CameraFrame,
+    noise: &mut Noise,
+    buffer: &mut Vec<u8>,
+    pub_: &mut Publisher<RosImage>,
+) -> Result<(), SensorError> {
     let slice = image.as_slice();
     if slice.is_empty() {
-        return;
+        return Ok(());
     }
     let width = image.width();
     let height = image.height();
-    let data: Vec<_> = slice
-        .iter()
-        .flat_map(|&Color { b, g, r, a }| [b, g, r, a])
-        .collect();
+
+    // Carla colors are stored in BGRA order, which is the layout of
+    // bgra8 images.
+    let mut data = mem::take(buffer);
+    data.clear();
+    data.extend_from_slice(as_bytes(slice));
+    noise.pixels(&mut data, 4);
 
     let msg = RosImage {
         header,
@@ -152,26 +1083,244 @@ fn camera_callback(header: Header, image: CarlaImage, pub_: &mut Publisher<RosIm
         data,
     };
 
-    pub_.publish(&msg).unwrap();
+    pub_.publish(&msg)?;
+    *buffer = msg.data;
+    Ok(())
 }
 
-fn lidar_callback(header: Header, measure: LidarMeasurement, pub_: &mut Publisher<PointCloud2>) {
-    let slice = measure.as_slice();
+fn depth_camera_callback(
+    header: Header,
+    image: CameraFrame,
+    depth_pub: &mut Publisher<RosImage>,
+    log_depth_pub: &mut Publisher<RosImage>,
+) -> Result<(), SensorError> {
+    let slice = image.as_slice();
     if slice.is_empty() {
-        return;
+        return Ok(());
     }
-    let point_step = mem::size_of_val(&slice[0]);
-    let row_step = slice.len();
-    let data: Vec<_> = slice
+    let width = image.width();
+    let height = image.height();
+    let depths: Vec<_> = slice.iter().map(decode_depth).collect();
+    let depth_data: Vec<_> = depths
         .iter()
-        .flat_map(
-            |&LidarDetection {
-                 point: Location { x, y, z },
-                 intensity,
-             }| { [x, y, z, intensity] },
-        )
-        .flat_map(|elem| elem.to_ne_bytes())
+        .flat_map(|depth| depth.to_ne_bytes())
+        .collect();
+    let log_depth_data: Vec<_> = depths.iter().map(|&depth| log_depth_gray(depth)).collect();
+
+    let depth_msg = RosImage {
+        header: header.clone(),
+        height: height as u32,
+        width: width as u32,
+        encoding: "32FC1".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: (width * 4) as u32,
+        data: depth_data,
+    };
+    let log_depth_msg = RosImage {
+        header,
+        height: height as u32,
+        width: width as u32,
+        encoding: "mono8".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: width as u32,
+        data: log_depth_data,
+    };
+
+    depth_pub.publish(&depth_msg)?;
+    log_depth_pub.publish(&log_depth_msg)?;
+    Ok(())
+}
+
+/// Decodes the depth in meters from a depth camera pixel.
+///
+/// Carla packs the normalized depth into the 24 bits of R, G and B
+/// channels, where R is the least significant byte and 1.0 stands for
+/// the far plane at 1000 meters.
+fn decode_depth(color: &Color) -> f32 {
+    let Color { r, g, b, .. } = *color;
+    let packed = r as f64 + g as f64 * 256.0 + b as f64 * 65536.0;
+    let normalized = packed / 16_777_215.0;
+    (normalized * MAX_DEPTH) as f32
+}
+
+/// Maps a depth in meters to the logarithmic grayscale level that
+/// Carla's `LogarithmicDepth` color converter produces.
+fn log_depth_gray(depth: f32) -> u8 {
+    let normalized = depth as f64 / MAX_DEPTH;
+    let level = (1.0 + normalized.ln() / 5.70378).clamp(0.0, 1.0);
+    (level * 255.0).round() as u8
+}
+
+fn semantic_camera_callback(
+    header: Header,
+    image: CameraFrame,
+    label_pub: &mut Publisher<RosImage>,
+    color_pub: &mut Publisher<RosImage>,
+) -> Result<(), SensorError> {
+    let slice = image.as_slice();
+    if slice.is_empty() {
+        return Ok(());
+    }
+    let width = image.width();
+    let height = image.height();
+
+    // The semantic tag is stored in the red channel.
+    let label_data: Vec<_> = slice.iter().map(|color| color.r).collect();
+    let color_data: Vec<_> = label_data
+        .iter()
+        .flat_map(|&tag| {
+            let [r, g, b] = SemanticTag::from_u8(tag)
+                .map(|tag| tag.cityscapes_color())
+                .unwrap_or_default();
+            [b, g, r]
+        })
         .collect();
+
+    let label_msg = RosImage {
+        header: header.clone(),
+        height: height as u32,
+        width: width as u32,
+        encoding: "mono8".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: width as u32,
+        data: label_data,
+    };
+    let color_msg = RosImage {
+        header,
+        height: height as u32,
+        width: width as u32,
+        encoding: "bgr8".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: (width * 3) as u32,
+        data: color_data,
+    };
+
+    label_pub.publish(&label_msg)?;
+    color_pub.publish(&color_msg)?;
+    Ok(())
+}
+
+/// Publishes the label and instance images of an instance
+/// segmentation camera.
+///
+/// Carla encodes the actor ID of each pixel in 16 bits, so instance
+/// IDs match the `id_<ACTOR_ID>` namespaces only for actor IDs up to
+/// 65535. Larger IDs are truncated, and objects that are not actors,
+/// such as static map props, carry IDs that match no actor.
+fn instance_camera_callback(
+    header: Header,
+    image: CameraFrame,
+    label_pub: &mut Publisher<RosImage>,
+    instance_pub: &mut Publisher<RosImage>,
+) -> Result<(), SensorError> {
+    let slice = image.as_slice();
+    if slice.is_empty() {
+        return Ok(());
+    }
+    let width = image.width();
+    let height = image.height();
+
+    // The semantic tag is stored in the red channel, while the
+    // low 16 bits of the actor ID are stored in the green (low byte)
+    // and blue (high byte) channels.
+    let label_data: Vec<_> = slice.iter().map(|color| color.r).collect();
+    let instance_data: Vec<_> = slice
+        .iter()
+        .flat_map(|&Color { g, b, .. }| u16::from_le_bytes([g, b]).to_ne_bytes())
+        .collect();
+
+    let label_msg = RosImage {
+        header: header.clone(),
+        height: height as u32,
+        width: width as u32,
+        encoding: "mono8".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: width as u32,
+        data: label_data,
+    };
+    let instance_msg = RosImage {
+        header,
+        height: height as u32,
+        width: width as u32,
+        encoding: "16UC1".to_string(),
+        is_bigendian: is_bigendian().into(),
+        step: (width * 2) as u32,
+        data: instance_data,
+    };
+
+    label_pub.publish(&label_msg)?;
+    instance_pub.publish(&instance_msg)?;
+    Ok(())
+}
+
+/// Builds the mapping from semantic tags to class names.
+fn semantic_label_info() -> LabelInfo {
+    LabelInfo {
+        class_map: SemanticTag::ALL
+            .iter()
+            .map(|tag| VisionClass {
+                class_id: *tag as u16,
+                class_name: tag.name().to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// The lidar blueprint attributes describing the laser arrangement.
+#[derive(Debug, Clone)]
+struct LidarConfig {
+    channels: u32,
+    /// The elevation of the highest laser in degrees.
+    upper_fov: f32,
+    /// The elevation of the lowest laser in degrees.
+    lower_fov: f32,
+    rotation_frequency: f64,
+}
+
+fn lidar_config(actor: &Sensor) -> LidarConfig {
+    LidarConfig {
+        channels: actor.parse_attribute("channels").unwrap_or(32),
+        upper_fov: actor.parse_attribute("upper_fov").unwrap_or(10.0),
+        lower_fov: actor.parse_attribute("lower_fov").unwrap_or(-30.0),
+        rotation_frequency: actor.parse_attribute("rotation_frequency").unwrap_or(10.0),
+    }
+}
+
+/// Publishes a lidar point cloud.
+///
+/// The points are expected to have the range noise applied already,
+/// while whole point clouds are dropped here.
+fn lidar_callback(
+    sweep: Sweep<'_, LidarDetection>,
+    layout: LidarPointLayout,
+    config: &LidarConfig,
+    noise: &mut Noise,
+    buffer: &mut Vec<u8>,
+    pub_: &mut Publisher<PointCloud2>,
+) -> Result<(), SensorError> {
+    if noise.drop_message() || sweep.points.is_empty() {
+        return Ok(());
+    }
+
+    let msg = match layout {
+        LidarPointLayout::Xyzi => lidar_xyzi_msg(sweep.header, &sweep.points, mem::take(buffer)),
+        LidarPointLayout::Xyzircaedt => lidar_xyzircaedt_msg(&sweep, config, mem::take(buffer)),
+    };
+    pub_.publish(&msg)?;
+    *buffer = msg.data;
+    Ok(())
+}
+
+fn lidar_xyzi_msg(header: Header, slice: &[LidarDetection], mut data: Vec<u8>) -> PointCloud2 {
+    // Carla detections have the same layout as the point cloud, so
+    // they are copied in bulk and converted in place.
+    let point_step = mem::size_of::<LidarDetection>();
+    data.clear();
+    data.extend_from_slice(as_bytes(slice));
+    coord::points_in_place(&mut data, point_step, 4);
+    let row_step = data.len();
+
     let fields = vec![
         PointField {
             name: "x".to_string(),
@@ -199,7 +1348,7 @@ fn lidar_callback(header: Header, measure: LidarMeasurement, pub_: &mut Publishe
         },
     ];
 
-    let msg = PointCloud2 {
+    PointCloud2 {
         header,
         height: 1,
         width: slice.len() as u32,
@@ -209,43 +1358,228 @@ fn lidar_callback(header: Header, measure: LidarMeasurement, pub_: &mut Publishe
         row_step: row_step as u32,
         data,
         is_dense: true,
+    }
+}
+
+/// Builds a point cloud in Autoware's PointXYZIRCAEDT layout.
+///
+/// The channel is the index of the laser counted from the highest
+/// one. The header is stamped with the time of the earliest ray, and
+/// the time stamp of each point is the offset in nanoseconds from
+/// it.
+fn lidar_xyzircaedt_msg(
+    sweep: &Sweep<'_, LidarDetection>,
+    config: &LidarConfig,
+    mut data: Vec<u8>,
+) -> PointCloud2 {
+    const POINT_STEP: usize = 32;
+
+    let LidarConfig {
+        channels,
+        upper_fov,
+        lower_fov,
+        rotation_frequency,
+    } = *config;
+    let channels = channels.max(1);
+
+    // Carla lasers are evenly spaced between the upper and lower FOV.
+    let channel_spacing = if channels > 1 {
+        (upper_fov - lower_fov) / (channels - 1) as f32
+    } else {
+        0.0
+    };
+    let to_channel = |elevation: f32| -> u16 {
+        if channel_spacing <= 0.0 {
+            return 0;
+        }
+        let index = ((upper_fov - elevation.to_degrees()) / channel_spacing).round();
+        index.clamp(0.0, (channels - 1) as f32) as u16
+    };
+
+    let points: Vec<_> = sweep
+        .points
+        .iter()
+        .map(
+            |&LidarDetection {
+                 point: Location { x, y, z },
+                 intensity,
+             }| {
+                let [x, y, z] = coord::point([x, y, z]);
+                let elevation = z.atan2(x.hypot(y));
+                LidarPoint {
+                    x,
+                    y,
+                    z,
+                    intensity,
+                    channel: to_channel(elevation),
+                    azimuth: y.atan2(x).rem_euclid(TAU),
+                    elevation,
+                    distance: (x * x + y * y + z * z).sqrt(),
+                }
+            },
+        )
+        .collect();
+
+    // Every slice is timed on its own, counting back from its last
+    // ray, so that points of overlapping slices get distinct times.
+    let secs_per_radian = 1.0 / (TAU as f64 * rotation_frequency);
+    let mut times = Vec::with_capacity(points.len());
+    let mut offset = 0;
+    for slice in &sweep.slices {
+        let end = (offset + slice.len).min(points.len());
+        let slice_points = &points[offset..end];
+        offset = end;
+
+        let start_azimuth = slice_start_azimuth(slice_points, channels);
+        let rotated: Vec<_> = slice_points
+            .iter()
+            .map(|point| (start_azimuth - point.azimuth).rem_euclid(TAU))
+            .collect();
+        let last_rotated = rotated.iter().copied().fold(0.0, f32::max);
+        times.extend(
+            rotated
+                .into_iter()
+                .map(|rotated| slice.end_time - (last_rotated - rotated) as f64 * secs_per_radian),
+        );
+    }
+    times.resize(points.len(), sweep.timestamp);
+    let start_time = times.iter().copied().fold(f64::INFINITY, f64::min);
+
+    let header = Header {
+        stamp: offset_stamp(&sweep.header.stamp, start_time - sweep.timestamp),
+        frame_id: sweep.header.frame_id.clone(),
     };
 
-    pub_.publish(&msg).unwrap();
+    let return_type = LidarReturnType::SINGLE_STRONGEST as u8;
+    data.clear();
+    data.reserve(points.len() * POINT_STEP);
+    for (point, time) in points.iter().zip(times) {
+        let LidarPoint {
+            x,
+            y,
+            z,
+            intensity,
+            channel,
+            azimuth,
+            elevation,
+            distance,
+        } = *point;
+        let time_stamp = ((time - start_time) * 1e9).round() as u32;
+        let intensity = (intensity.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        data.extend_from_slice(&x.to_ne_bytes());
+        data.extend_from_slice(&y.to_ne_bytes());
+        data.extend_from_slice(&z.to_ne_bytes());
+        data.push(intensity);
+        data.push(return_type);
+        data.extend_from_slice(&channel.to_ne_bytes());
+        data.extend_from_slice(&azimuth.to_ne_bytes());
+        data.extend_from_slice(&elevation.to_ne_bytes());
+        data.extend_from_slice(&distance.to_ne_bytes());
+        data.extend_from_slice(&time_stamp.to_ne_bytes());
+    }
+
+    let fields = [
+        ("x", 0, PointFieldType::FLOAT32),
+        ("y", 4, PointFieldType::FLOAT32),
+        ("z", 8, PointFieldType::FLOAT32),
+        ("intensity", 12, PointFieldType::UINT8),
+        ("return_type", 13, PointFieldType::UINT8),
+        ("channel", 14, PointFieldType::UINT16),
+        ("azimuth", 16, PointFieldType::FLOAT32),
+        ("elevation", 20, PointFieldType::FLOAT32),
+        ("distance", 24, PointFieldType::FLOAT32),
+        ("time_stamp", 28, PointFieldType::UINT32),
+    ]
+    .into_iter()
+    .map(|(name, offset, datatype)| PointField {
+        name: name.to_string(),
+        offset,
+        datatype: datatype as u8,
+        count: 1,
+    })
+    .collect();
+
+    PointCloud2 {
+        header,
+        height: 1,
+        width: points.len() as u32,
+        fields,
+        is_bigendian: is_bigendian(),
+        point_step: POINT_STEP as u32,
+        row_step: (points.len() * POINT_STEP) as u32,
+        data,
+        is_dense: true,
+    }
+}
+
+/// Finds the azimuth of the earliest ray in a slice.
+///
+/// The lidar spins clockwise in the ROS frame, so the azimuth
+/// decreases over time. Every laser sweeps the same horizontal
+/// angles, and Carla stores the rays of each laser in the scanning
+/// order. The earliest ray is thus found among the first points of
+/// each channel.
+fn slice_start_azimuth(points: &[LidarPoint], channels: u32) -> f32 {
+    let mut first_azimuths = vec![None; channels as usize];
+    for point in points {
+        first_azimuths[point.channel as usize].get_or_insert(point.azimuth);
+    }
+    first_azimuths
+        .into_iter()
+        .flatten()
+        .reduce(|lhs, rhs| {
+            let diff = (rhs - lhs + PI).rem_euclid(TAU) - PI;
+            if diff > 0.0 {
+                rhs
+            } else {
+                lhs
+            }
+        })
+        .unwrap_or(0.0)
+}
+
+/// Shifts a ROS time by an offset in seconds.
+fn offset_stamp(stamp: &Time, offset: f64) -> Time {
+    let nanos = stamp.sec as i64 * 1_000_000_000 + stamp.nanosec as i64;
+    let nanos = (nanos + (offset * 1e9).round() as i64).max(0);
+    Time {
+        sec: (nanos / 1_000_000_000) as i32,
+        nanosec: (nanos % 1_000_000_000) as u32,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LidarPoint {
+    x: f32,
+    y: f32,
+    z: f32,
+    intensity: f32,
+    channel: u16,
+    azimuth: f32,
+    elevation: f32,
+    distance: f32,
 }
 
 fn semantic_lidar_callback(
     header: Header,
-    measure: SemanticLidarMeasurement,
+    slice: &[SemanticLidarDetection],
+    buffer: &mut Vec<u8>,
     pub_: &mut Publisher<PointCloud2>,
-) {
-    let slice = measure.as_slice();
+) -> Result<(), SensorError> {
     if slice.is_empty() {
-        return;
+        return Ok(());
     }
-    let point_step = mem::size_of_val(&slice[0]);
-    let row_step = slice.len();
-    let data: Vec<_> = slice
-        .iter()
-        .flat_map(
-            |&SemanticLidarDetection {
-                 point: Location { x, y, z },
-                 cos_inc_angle,
-                 object_idx,
-                 object_tag,
-             }| {
-                [
-                    x.to_ne_bytes(),
-                    y.to_ne_bytes(),
-                    z.to_ne_bytes(),
-                    cos_inc_angle.to_ne_bytes(),
-                    object_idx.to_ne_bytes(),
-                    object_tag.to_ne_bytes(),
-                ]
-            },
-        )
-        .flatten()
-        .collect();
+
+    // Carla detections have the same layout as the point cloud, so
+    // they are copied in bulk and converted in place.
+    let point_step = mem::size_of::<SemanticLidarDetection>();
+    let mut data = mem::take(buffer);
+    data.clear();
+    data.extend_from_slice(as_bytes(slice));
+    coord::points_in_place(&mut data, point_step, 4);
+    let row_step = data.len();
+
     let fields = vec![
         PointField {
             name: "x".to_string(),
@@ -297,32 +1631,328 @@ fn semantic_lidar_callback(
         is_dense: true,
     };
 
-    pub_.publish(&msg).unwrap();
+    pub_.publish(&msg)?;
+    *buffer = msg.data;
+    Ok(())
+}
+
+fn radar_callback(
+    header: Header,
+    measure: RadarMeasurement,
+    pointcloud_pub: &mut Publisher<PointCloud2>,
+    scan_pub: &mut Publisher<RadarScan>,
+) -> Result<(), SensorError> {
+    let slice = measure.as_slice();
+    if slice.is_empty() {
+        return Ok(());
+    }
+
+    // Convert polar detections to Cartesian points.
+    let points: Vec<_> = slice
+        .iter()
+        .map(
+            |&RadarDetection {
+                 velocity,
+                 azimuth,
+                 altitude,
+                 depth,
+             }| {
+                let x = depth * altitude.cos() * azimuth.cos();
+                let y = depth * altitude.cos() * azimuth.sin();
+                let z = depth * altitude.sin();
+                let [x, y, z] = coord::point([x, y, z]);
+                [x, y, z, velocity]
+            },
+        )
+        .collect();
+    let point_step = mem::size_of_val(&points[0]);
+    let data: Vec<_> = points
+        .iter()
+        .flatten()
+        .flat_map(|elem| elem.to_ne_bytes())
+        .collect();
+    let row_step = data.len();
+    let fields = vec![
+        PointField {
+            name: "x".to_string(),
+            offset: 0,
+            datatype: PointFieldType::FLOAT32 as u8,
+            count: 1,
+        },
+        PointField {
+            name: "y".to_string(),
+            offset: 4,
+            datatype: PointFieldType::FLOAT32 as u8,
+            count: 1,
+        },
+        PointField {
+            name: "z".to_string(),
+            offset: 8,
+            datatype: PointFieldType::FLOAT32 as u8,
+            count: 1,
+        },
+        PointField {
+            name: "velocity".to_string(),
+            offset: 12,
+            datatype: PointFieldType::FLOAT32 as u8,
+            count: 1,
+        },
+    ];
+
+    let pointcloud_msg = PointCloud2 {
+        header: header.clone(),
+        height: 1,
+        width: points.len() as u32,
+        fields,
+        is_bigendian: is_bigendian(),
+        point_step: point_step as u32,
+        row_step: row_step as u32,
+        data,
+        is_dense: true,
+    };
+
+    let returns = slice
+        .iter()
+        .map(
+            |&RadarDetection {
+                 velocity,
+                 azimuth,
+                 altitude,
+                 depth,
+             }| RadarReturn {
+                range: depth,
+                azimuth: coord::azimuth(azimuth),
+                elevation: altitude,
+                doppler_velocity: velocity,
+                amplitude: 0.0,
+            },
+        )
+        .collect();
+    let scan_msg = RadarScan { header, returns };
+
+    pointcloud_pub.publish(&pointcloud_msg)?;
+    scan_pub.publish(&scan_msg)?;
+    Ok(())
+}
+
+/// Standard deviations of GNSS noise given by the sensor blueprint.
+///
+/// The latitude and longitude deviations are in degrees, while the
+/// altitude deviation is in meters.
+struct GnssStddev {
+    lat: f64,
+    lon: f64,
+    alt: f64,
+}
+
+fn gnss_callback(
+    header: Header,
+    measure: GnssMeasurement,
+    stddev: &GnssStddev,
+    pub_: &mut Publisher<NavSatFix>,
+) -> Result<(), SensorError> {
+    let latitude = measure.latitude();
+    let longitude = measure.longitude();
+    let altitude = measure.altitude();
+
+    // Convert angular deviations to meters in the ENU frame.
+    let north_stddev = stddev.lat.to_radians() * EARTH_RADIUS;
+    let east_stddev = stddev.lon.to_radians() * EARTH_RADIUS * latitude.to_radians().cos();
+    let up_stddev = stddev.alt;
+
+    let (position_covariance, covariance_type) =
+        if north_stddev == 0.0 && east_stddev == 0.0 && up_stddev == 0.0 {
+            (vec![0.0; 9], NavSatCovarianceType::UNKNOWN)
+        } else {
+            #[rustfmt::skip]
+            let covariance = vec![
+                east_stddev.powi(2), 0.0, 0.0,
+                0.0, north_stddev.powi(2), 0.0,
+                0.0, 0.0, up_stddev.powi(2),
+            ];
+            (covariance, NavSatCovarianceType::DIAGONAL_KNOWN)
+        };
+
+    let msg = NavSatFix {
+        header,
+        status: NavSatStatus {
+            status: NavSatStatusType::FIX as i8,
+            service: NavSatServiceType::GPS as u16,
+        },
+        latitude,
+        longitude,
+        altitude,
+        position_covariance,
+        position_covariance_type: covariance_type as u8,
+    };
+
+    pub_.publish(&msg)?;
+    Ok(())
+}
+
+/// Covariance matrices of IMU readings in row-major order.
+struct ImuCovariance {
+    orientation: Vec<f64>,
+    angular_velocity: Vec<f64>,
+    linear_acceleration: Vec<f64>,
 }
 
-fn imu_callback(header: Header, measure: ImuMeasurement, pub_: &mut Publisher<Imu>) {
-    let accel = measure.accelerometer();
-    let compass = measure.compass();
-    let orientation = UnitQuaternion::from_euler_angles(0.0, 0.0, -compass);
-    let gyro = measure.gyroscope();
+/// Builds IMU covariances from the noise attributes of the sensor
+/// blueprint.
+///
+/// The gyroscope bias is a constant offset in Carla, so it is counted
+/// in the angular velocity variance. The orientation comes from the
+/// sensor transform without noise, so its variance is a negligible
+/// [IMU_ORIENTATION_VARIANCE]. The white noise injected by the
+/// bridge is added on top, while its bias random walk is left out
+/// since it grows without bound.
+fn imu_covariance(actor: &Sensor, noise: &NoiseParams) -> ImuCovariance {
+    let get = |id: &str| -> f64 { actor.parse_attribute(id).unwrap_or(0.0) };
+    let accel_var = ["x", "y", "z"].map(|axis| {
+        get(&format!("noise_accel_stddev_{axis}")).powi(2) + noise.imu_accel_stddev.powi(2)
+    });
+    let gyro_var = ["x", "y", "z"].map(|axis| {
+        get(&format!("noise_gyro_stddev_{axis}")).powi(2)
+            + get(&format!("noise_gyro_bias_{axis}")).powi(2)
+            + noise.imu_gyro_stddev.powi(2)
+    });
+
+    ImuCovariance {
+        orientation: diagonal_matrix(&[IMU_ORIENTATION_VARIANCE; 3]).into_raw_vec(),
+        angular_velocity: diagonal_matrix(&gyro_var).into_raw_vec(),
+        linear_acceleration: diagonal_matrix(&accel_var).into_raw_vec(),
+    }
+}
+
+fn imu_callback(
+    header: Header,
+    measure: ImuMeasurement,
+    covariance: &ImuCovariance,
+    noise: &mut Noise,
+    pub_: &mut Publisher<Imu>,
+) -> Result<(), SensorError> {
+    let (accel, gyro) = noise.imu(
+        measure.timestamp(),
+        measure.accelerometer(),
+        measure.gyroscope(),
+    );
+    let orientation = coord::rotation(&measure.sensor_transform().rotation);
 
     let msg = Imu {
         header,
         orientation: orientation.to_ros_type(),
-        orientation_covariance: identity_matrix(3).into_raw_vec(),
-        angular_velocity: gyro.to_ros_type(),
-        angular_velocity_covariance: identity_matrix(3).into_raw_vec(),
-        linear_acceleration: accel.to_ros_type(),
-        linear_acceleration_covariance: identity_matrix(3).into_raw_vec(),
+        orientation_covariance: covariance.orientation.clone(),
+        angular_velocity: coord::axial_vector(&gyro).to_ros_type(),
+        angular_velocity_covariance: covariance.angular_velocity.clone(),
+        linear_acceleration: coord::vector(&accel).to_ros_type(),
+        linear_acceleration_covariance: covariance.linear_acceleration.clone(),
     };
 
-    pub_.publish(&msg).unwrap();
+    pub_.publish(&msg)?;
+    Ok(())
 }
 
-fn collision_callback(header: Header, _event: CollisionEvent, pub_: &mut Publisher<Time>) {
-    pub_.publish(&header.stamp).unwrap();
+fn collision_callback(
+    header: Header,
+    event: CollisionEvent,
+    pub_: &mut Publisher<CollisionEventMsg>,
+) -> Result<(), SensorError> {
+    // The actor is the one the sensor is attached to.
+    let other_actor = event.other_actor();
+    let normal_impulse = coord::vector(&event.normal_impulse());
+
+    let msg = CollisionEventMsg {
+        header,
+        actor_id: event.actor().id(),
+        other_actor_id: other_actor.as_ref().map(|actor| actor.id()).unwrap_or(0),
+        other_actor_type_id: other_actor
+            .as_ref()
+            .map(|actor| actor.type_id())
+            .unwrap_or_default(),
+        normal_impulse: normal_impulse.to_ros_type(),
+        normal_impulse_magnitude: normal_impulse.norm() as f64,
+    };
+    pub_.publish(&msg)?;
+    Ok(())
 }
 
+fn lane_invasion_callback(
+    header: Header,
+    event: LaneInvasionEvent,
+    pub_: &mut Publisher<CarlaLaneInvasionEvent>,
+) -> Result<(), SensorError> {
+    let crossed_lane_markings = event
+        .crossed_lane_markings()
+        .iter()
+        .map(|marking| marking.type_() as i32)
+        .collect();
+    let msg = CarlaLaneInvasionEvent {
+        header,
+        crossed_lane_markings,
+    };
+    pub_.publish(&msg)?;
+    Ok(())
+}
+
+fn obstacle_callback(
+    header: Header,
+    event: ObstacleDetectionEvent,
+    pub_: &mut Publisher<ObstacleDetectionEventMsg>,
+) -> Result<(), SensorError> {
+    // The actor is the one the sensor is attached to.
+    let other_actor = event.other_actor();
+
+    let msg = ObstacleDetectionEventMsg {
+        header,
+        actor_id: event.actor().id(),
+        other_actor_id: other_actor.as_ref().map(|actor| actor.id()).unwrap_or(0),
+        other_actor_type_id: other_actor
+            .as_ref()
+            .map(|actor| actor.type_id())
+            .unwrap_or_default(),
+        distance: event.distance(),
+    };
+    pub_.publish(&msg)?;
+    Ok(())
+}
+
+// The records of Carla sensor buffers are copied as raw bytes. Make
+// sure they consist of packed 4-byte fields.
+const _: () = assert!(mem::size_of::<Color>() == 4);
+const _: () = assert!(mem::size_of::<LidarDetection>() == 16);
+const _: () = assert!(mem::size_of::<SemanticLidarDetection>() == 24);
+
+/// Views Carla sensor records as raw bytes.
+fn as_bytes<T>(slice: &[T]) -> &[u8]
+where
+    T: SensorRecord,
+{
+    // SAFETY: The implementors of `SensorRecord` are plain data
+    // without padding, as required by the trait.
+    unsafe { std::slice::from_raw_parts(slice.as_ptr().cast(), mem::size_of_val(slice)) }
+}
+
+/// Carla sensor records that can be viewed as raw bytes.
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` plain data without padding, and its
+/// fields must be laid out in the order of the wire format the bytes
+/// are published in. The size assertions above catch padding but not
+/// the field order.
+unsafe trait SensorRecord {}
+
+// SAFETY: Carla stores colors as four `u8` fields in BGRA order, which
+// is the layout of bgra8 images.
+unsafe impl SensorRecord for Color {}
+// SAFETY: The `f32` x, y and z fields are followed by the `f32`
+// intensity, which is the layout of xyzi point clouds.
+unsafe impl SensorRecord for LidarDetection {}
+// SAFETY: The `f32` x, y and z fields are followed by the `f32`
+// cosine of the incidence angle and the `u32` object index and tag,
+// which is the layout of semantic point clouds.
+unsafe impl SensorRecord for SemanticLidarDetection {}
+
 const fn is_bigendian() -> bool {
     cfg!(target_endian = "big")
 }
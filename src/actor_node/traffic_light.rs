@@ -1,5 +1,6 @@
 use super::odom::OdomPub;
 use crate::{
+    bridge::TickMessages,
     qos,
     types::{TrafficLightColor, TrafficLightShape, TrafficLightStatus},
     utils::ToRosType,
@@ -26,7 +27,7 @@ pub fn new(
     let status_pub = node.create_publisher(&format!("{prefix}/status"), qos::best_effort())?;
     let trigger_volume_pub =
         node.create_publisher(&format!("{prefix}/trigger_volume"), qos::best_effort())?;
-    let odom_pub = OdomPub::new(node, actor.clone(), &prefix)?;
+    let odom_pub = OdomPub::new(node, actor.clone(), &prefix, true)?;
     let pub_ = TrafficLightPub {
         actor,
         odom_pub,
@@ -45,7 +46,7 @@ pub struct TrafficLightPub {
 }
 
 impl TrafficLightPub {
-    pub fn poll(&mut self, time: &Time) -> Result<()> {
+    pub fn poll(&mut self, time: &Time, tick_messages: &mut TickMessages) -> Result<()> {
         let bbox = self.actor.trigger_volume();
         let (rbit, ybit, gbit) = match self.actor.state() {
             TrafficLightState::Red => (true, false, false),
@@ -97,7 +98,7 @@ impl TrafficLightPub {
         };
         let bbox_msg = bbox.to_ros_type();
 
-        self.odom_pub.poll(time)?;
+        self.odom_pub.poll(time, tick_messages)?;
         self.status_pub.publish(&status_msg)?;
         self.trigger_volume_pub.publish(&bbox_msg)?;
 
@@ -1,5 +1,5 @@
 use super::odom::OdomPub;
-use crate::{qos, utils::ToRosType};
+use crate::{bridge::TickMessages, qos, utils::ToRosType};
 use anyhow::Result;
 use carla::client::{ActorBase, TrafficSign};
 use r2r::{builtin_interfaces::msg::Time, moveit_msgs::msg::OrientedBoundingBox, Node, Publisher};
@@ -7,7 +7,7 @@ use r2r::{builtin_interfaces::msg::Time, moveit_msgs::msg::OrientedBoundingBox,
 pub fn new(node: &mut Node, actor: TrafficSign) -> Result<(TrafficSignPub, TrafficSignSub)> {
     let actor_id = actor.id();
     let prefix = format!("traffic_sign/id_{actor_id}");
-    let odom_pub = OdomPub::new(node, actor.clone(), &prefix)?;
+    let odom_pub = OdomPub::new(node, actor.clone(), &prefix, true)?;
     let trigger_volume_pub =
         node.create_publisher(&format!("{prefix}/trigger_volume"), qos::best_effort())?;
     let pub_ = TrafficSignPub {
@@ -26,12 +26,12 @@ pub struct TrafficSignPub {
 }
 
 impl TrafficSignPub {
-    pub fn poll(&mut self, time: &Time) -> Result<()> {
+    pub fn poll(&mut self, time: &Time, tick_messages: &mut TickMessages) -> Result<()> {
         let bbox = self.actor.trigger_volume();
         let bbox_msg = bbox.to_ros_type();
 
         self.trigger_volume_pub.publish(&bbox_msg)?;
-        self.odom_pub.poll(time)?;
+        self.odom_pub.poll(time, tick_messages)?;
         Ok(())
     }
 }
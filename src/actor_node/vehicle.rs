@@ -1,26 +1,53 @@
 use super::odom::OdomPub;
-use crate::{qos, time::TimeDelta, utils::ToRosType};
+use crate::{
+    bridge::TickMessages,
+    coord,
+    noise::Noise,
+    params::Params,
+    qos,
+    time::TimeDelta,
+    types::{
+        ControlModeType, GearType, HazardLightsReportType, LongitudinalMode,
+        TurnIndicatorsReportType,
+    },
+    utils::ToRosType,
+};
 use anyhow::{anyhow, Result};
 use carla::{
     client::{ActorBase, Vehicle},
     geom::Vector3DExt,
-    rpc::{VehicleControl, VehiclePhysicsControl, WheelPhysicsControl},
+    rpc::{VehicleControl, VehicleLightState, VehiclePhysicsControl, WheelPhysicsControl},
 };
 use carla_ackermann::{
     vehicle_control::{Output, TargetRequest},
     VehicleController,
 };
-use futures::{future::BoxFuture, stream, FutureExt, Stream, StreamExt};
+use futures::{
+    future::{self, BoxFuture},
+    stream, FutureExt, Stream, StreamExt,
+};
+use num_traits::FromPrimitive;
 use r2r::{
     autoware_control_msgs::msg::{Control, Lateral, Longitudinal},
+    autoware_vehicle_msgs::msg::{
+        ControlModeReport, GearCommand, GearReport, HazardLightsReport, SteeringReport,
+        TurnIndicatorsReport, VelocityReport,
+    },
     builtin_interfaces::msg::Time,
     carla_msgs::msg::{CarlaEgoVehicleControl, CarlaEgoVehicleInfo, CarlaEgoVehicleInfoWheel},
-    log_warn, Node, Publisher,
+    log_warn,
+    std_msgs::msg::Header,
+    Node, Publisher,
 };
-use std::{future::IntoFuture, sync::Once};
+use std::{future::IntoFuture, sync::Once, time::Duration};
 use tokio::{spawn, sync::watch};
 
-pub fn new(node: &mut Node, actor: Vehicle) -> Result<(VehiclePub, VehicleSub)> {
+/// The smallest target acceleration magnitude in m/s² passed to the
+/// controller, which reads values near zero as its maximum
+/// acceleration.
+const MIN_CONTROLLER_ACCEL: f64 = 1e-3;
+
+pub fn new(node: &mut Node, actor: Vehicle, params: &Params) -> Result<(VehiclePub, VehicleSub)> {
     let role_name = actor
         .attributes()
         .iter()
@@ -32,27 +59,59 @@ pub fn new(node: &mut Node, actor: Vehicle) -> Result<(VehiclePub, VehicleSub)>
     let physics_control = actor.physics_control();
     let controller = VehicleController::from_physics_control(&physics_control, None);
 
+    let longitudinal_mode = params.longitudinal_mode(&role_name);
     let prefix = format!("vehicle/{role_name}");
     let vehicle_info_pub =
         node.create_publisher(&format!("{prefix}/vehicle_info"), qos::latched())?;
-    let odom_pub = OdomPub::new(node, actor.clone(), &prefix)?;
+    let odom_pub = OdomPub::new(node, actor.clone(), &prefix, true)?
+        .with_noise(Noise::new(&params.noise, actor.id()));
+    let status_pub = VehicleStatusPub::new(node, &prefix)?;
+
+    // The steering tire angle at full steer is the largest among the
+    // wheels, which is the one of the front wheels.
+    let max_steer_angle = physics_control
+        .wheels
+        .iter()
+        .map(|wheel| wheel.max_steer_angle)
+        .fold(0.0, f32::max)
+        .to_radians();
 
     let control_sub = node.subscribe(&format!("{prefix}/control_cmd"), qos::best_effort())?;
     let ackermann_sub = node.subscribe(&format!("{prefix}/ackermann_cmd"), qos::best_effort())?;
     let forward_control = spawn(forward_control_cmd(control_sub, ackermann_sub, control_tx))
         .map(|result| result.unwrap());
 
+    let (gear_tx, gear_rx) = watch::channel(None);
+    let gear_sub = node.subscribe(&format!("{prefix}/gear_cmd"), qos::best_effort())?;
+    let forward_gear = spawn(forward_gear_cmd(gear_sub, gear_tx)).map(|result| result.unwrap());
+
     let pub_ = VehiclePub {
         actor,
         role_name,
         odom_pub,
+        status_pub,
         vehicle_info_pub,
         controller,
         physics_control,
         control_rx,
+        control_timeout: Duration::from_secs_f64(params.control_timeout),
+        last_command_time: None,
+        max_steering_rate: params.max_steering_tire_rotation_rate,
+        steering_angle: None,
+        default_jerk_limit: params.default_jerk_limit,
+        accel: None,
+        longitudinal_mode,
+        frame_id: prefix,
+        max_steer_angle,
+        applied_control: None,
+        applied_gear: None,
+        gear_rx,
+        gear: None,
     };
     let sub = VehicleSub {
-        future: forward_control.boxed(),
+        future: future::join(forward_control, forward_gear)
+            .map(|_| ())
+            .boxed(),
     };
 
     // Publish vehicle info once
@@ -69,6 +128,74 @@ pub struct VehiclePub {
     vehicle_info_pub: Publisher<CarlaEgoVehicleInfo>,
     controller: VehicleController,
     control_rx: watch::Receiver<Option<ControlKind>>,
+    /// The simulation time after which the latest command is stale.
+    control_timeout: Duration,
+    /// The simulation time when the latest command is received.
+    last_command_time: Option<Duration>,
+    /// The physical maximum steering tire rotation rate in rad/s.
+    max_steering_rate: Option<f64>,
+    /// The rate-limited steering tire angle commanded in the previous
+    /// tick. It is unknown until an Ackermann command is applied.
+    steering_angle: Option<f64>,
+    /// The jerk limit in m/s³ used if the command does not define one.
+    default_jerk_limit: Option<f64>,
+    /// The jerk-limited target acceleration in the previous tick. It
+    /// is unknown until an Ackermann command requests an
+    /// acceleration.
+    accel: Option<f64>,
+    longitudinal_mode: LongitudinalMode,
+    status_pub: VehicleStatusPub,
+    frame_id: String,
+    /// The steering tire angle in radians at full steer.
+    max_steer_angle: f32,
+    /// The control applied in the latest tick, if any.
+    applied_control: Option<VehicleControl>,
+    /// The gear command applied to the latest control, if any.
+    applied_gear: Option<GearType>,
+    gear_rx: watch::Receiver<Option<GearType>>,
+    /// The latest gear command, if any.
+    gear: Option<GearType>,
+}
+
+/// Publishes the vehicle status reports expected by Autoware's
+/// vehicle interface.
+struct VehicleStatusPub {
+    velocity: Publisher<VelocityReport>,
+    steering: Publisher<SteeringReport>,
+    gear: Publisher<GearReport>,
+    control_mode: Publisher<ControlModeReport>,
+    turn_indicators: Publisher<TurnIndicatorsReport>,
+    hazard_lights: Publisher<HazardLightsReport>,
+}
+
+impl VehicleStatusPub {
+    fn new(node: &mut Node, prefix: &str) -> Result<Self> {
+        let velocity =
+            node.create_publisher(&format!("{prefix}/status/velocity_status"), qos::reliable())?;
+        let steering =
+            node.create_publisher(&format!("{prefix}/status/steering_status"), qos::reliable())?;
+        let gear =
+            node.create_publisher(&format!("{prefix}/status/gear_status"), qos::reliable())?;
+        let control_mode =
+            node.create_publisher(&format!("{prefix}/status/control_mode"), qos::reliable())?;
+        let turn_indicators = node.create_publisher(
+            &format!("{prefix}/status/turn_indicators_status"),
+            qos::reliable(),
+        )?;
+        let hazard_lights = node.create_publisher(
+            &format!("{prefix}/status/hazard_lights_status"),
+            qos::reliable(),
+        )?;
+
+        Ok(Self {
+            velocity,
+            steering,
+            gear,
+            control_mode,
+            turn_indicators,
+            hazard_lights,
+        })
+    }
 }
 
 pub struct VehicleSub {
@@ -85,17 +212,66 @@ impl IntoFuture for VehicleSub {
 }
 
 impl VehiclePub {
-    pub fn poll(&mut self, ros_time: &Time, time_delta: TimeDelta) -> Result<()> {
-        let control_msg = (*self.control_rx.borrow()).clone();
+    pub fn poll(
+        &mut self,
+        ros_time: &Time,
+        time_delta: TimeDelta,
+        tick_messages: &mut TickMessages,
+    ) -> Result<()> {
+        if self.control_rx.has_changed().unwrap_or(false) {
+            self.last_command_time = Some(time_delta.time);
+        }
+        let control_msg = (*self.control_rx.borrow_and_update()).clone();
+        self.gear = *self.gear_rx.borrow();
+
+        // The latest command keeps being applied, while it is reported
+        // as no command once it is stale. The simulation time restarts
+        // when the world is reloaded, which also makes it stale.
+        let is_stale = match self.last_command_time {
+            Some(last) => time_delta.time < last || time_delta.time - last > self.control_timeout,
+            None => true,
+        };
 
-        if let Some(command) = control_msg {
-            match command {
-                ControlKind::Direct(msg) => self.apply_direct_control(msg),
-                ControlKind::Ackermann(msg) => self.apply_ackermann_control(msg, time_delta),
+        let control_mode = match control_msg {
+            Some(ControlKind::Direct(msg)) => {
+                self.apply_direct_control(msg);
+                if is_stale {
+                    ControlModeType::NO_COMMAND
+                } else {
+                    ControlModeType::MANUAL
+                }
             }
-        }
+            Some(ControlKind::Ackermann(msg)) => {
+                self.apply_ackermann_control(msg, time_delta);
+                if is_stale {
+                    ControlModeType::NO_COMMAND
+                } else {
+                    ControlModeType::AUTONOMOUS
+                }
+            }
+            None => {
+                // Hold the vehicle in park even without control
+                // commands.
+                if self.gear == Some(GearType::PARK) {
+                    self.apply_control(
+                        VehicleControl {
+                            throttle: 0.0,
+                            steer: 0.0,
+                            brake: 0.0,
+                            hand_brake: true,
+                            reverse: false,
+                            manual_gear_shift: false,
+                            gear: 0,
+                        },
+                        self.gear,
+                    );
+                }
+                ControlModeType::NO_COMMAND
+            }
+        };
 
-        self.odom_pub.poll(ros_time)?;
+        self.odom_pub.poll(ros_time, tick_messages)?;
+        self.publish_status(ros_time, control_mode)?;
 
         Ok(())
     }
@@ -141,7 +317,7 @@ impl VehiclePub {
                         radius,
                         max_brake_torque,
                         max_handbrake_torque,
-                        position: position.to_na().to_ros_type(),
+                        position: coord::vector(&position.to_na()).to_ros_type(),
                     }
                 })
                 .collect(),
@@ -155,13 +331,108 @@ impl VehiclePub {
             clutch_strength,
             mass,
             drag_coefficient,
-            center_of_mass: center_of_mass.vector.to_ros_type(),
+            center_of_mass: coord::vector(&center_of_mass.vector).to_ros_type(),
         };
 
         self.vehicle_info_pub.publish(&vehicle_info_msg)?;
         Ok(())
     }
 
+    /// Publishes the Autoware vehicle status reports.
+    ///
+    /// The steering report is the steering tire angle commanded by the
+    /// applied control, `steer` times the maximum steering angle,
+    /// rather than the measured wheel angle.
+    fn publish_status(&self, ros_time: &Time, control_mode: ControlModeType) -> Result<()> {
+        let pubs = &self.status_pub;
+
+        // Velocities are reported in the vehicle frame.
+        let transform = coord::isometry(&self.actor.transform());
+        let velocity = coord::vector(&self.actor.velocity());
+        let angular_velocity = coord::angular_velocity_degrees(&self.actor.angular_velocity());
+        let velocity = transform.rotation.inverse_transform_vector(&velocity);
+        let angular_velocity = transform
+            .rotation
+            .inverse_transform_vector(&angular_velocity);
+        pubs.velocity.publish(&VelocityReport {
+            header: Header {
+                stamp: ros_time.clone(),
+                frame_id: self.frame_id.clone(),
+            },
+            longitudinal_velocity: velocity.x,
+            lateral_velocity: velocity.y,
+            heading_rate: angular_velocity.z,
+        })?;
+
+        // Carla steers right for positive values, while the tire angle
+        // is positive to the left.
+        // The commanded gear is reported if it is applied. Otherwise,
+        // the gear is derived from the control.
+        let (steer, gear) = match &self.applied_control {
+            Some(control) => {
+                let gear = if let Some(gear) = self.applied_gear {
+                    gear
+                } else if control.reverse {
+                    GearType::REVERSE
+                } else if control.hand_brake {
+                    GearType::PARK
+                } else if control.manual_gear_shift && control.gear == 0 {
+                    GearType::NEUTRAL
+                } else {
+                    GearType::DRIVE
+                };
+                (control.steer, gear)
+            }
+            None => (0.0, GearType::NONE),
+        };
+        pubs.steering.publish(&SteeringReport {
+            stamp: ros_time.clone(),
+            steering_tire_angle: -steer * self.max_steer_angle,
+        })?;
+        pubs.gear.publish(&GearReport {
+            stamp: ros_time.clone(),
+            report: gear as u8,
+        })?;
+
+        pubs.control_mode.publish(&ControlModeReport {
+            stamp: ros_time.clone(),
+            mode: control_mode as u8,
+        })?;
+
+        // Both blinkers are reported as hazard lights.
+        let light_state = self.actor.light_state();
+        let left = light_state.contains(VehicleLightState::LEFT_BLINKER);
+        let right = light_state.contains(VehicleLightState::RIGHT_BLINKER);
+        let (turn_indicators, hazard_lights) = match (left, right) {
+            (true, true) => (
+                TurnIndicatorsReportType::DISABLE,
+                HazardLightsReportType::ENABLE,
+            ),
+            (true, false) => (
+                TurnIndicatorsReportType::ENABLE_LEFT,
+                HazardLightsReportType::DISABLE,
+            ),
+            (false, true) => (
+                TurnIndicatorsReportType::ENABLE_RIGHT,
+                HazardLightsReportType::DISABLE,
+            ),
+            (false, false) => (
+                TurnIndicatorsReportType::DISABLE,
+                HazardLightsReportType::DISABLE,
+            ),
+        };
+        pubs.turn_indicators.publish(&TurnIndicatorsReport {
+            stamp: ros_time.clone(),
+            report: turn_indicators as u8,
+        })?;
+        pubs.hazard_lights.publish(&HazardLightsReport {
+            stamp: ros_time.clone(),
+            report: hazard_lights as u8,
+        })?;
+
+        Ok(())
+    }
+
     fn apply_direct_control(&mut self, msg: CarlaEgoVehicleControl) {
         let CarlaEgoVehicleControl {
             throttle,
@@ -173,22 +444,46 @@ impl VehiclePub {
             manual_gear_shift,
             ..
         } = msg;
-        self.actor.apply_control(&VehicleControl {
-            throttle,
-            steer,
-            brake,
-            hand_brake,
-            reverse,
-            manual_gear_shift,
-            gear,
-        });
+
+        // The steering angle and acceleration are unknown under direct
+        // control.
+        self.steering_angle = None;
+        self.accel = None;
+
+        // The gear fields of the message take precedence over the gear
+        // command.
+        self.apply_control(
+            VehicleControl {
+                throttle,
+                steer,
+                brake,
+                hand_brake,
+                reverse,
+                manual_gear_shift,
+                gear,
+            },
+            None,
+        );
     }
 
+    /// Applies an Ackermann command through the vehicle controller.
+    ///
+    /// The longitudinal mode maps the command onto the controller
+    /// target as follows, where the acceleration is jerk-limited.
+    ///
+    /// - Velocity: the speed is `velocity`, and the controller
+    ///   accelerates up to its maximum. The jerk is not limited.
+    /// - Acceleration: the speed is the one reached within the tick by
+    ///   `acceleration` along the direction of travel, and the
+    ///   acceleration limits how fast it is reached.
+    /// - Both: the speed is `velocity`, and the acceleration limits
+    ///   how fast it is reached.
     fn apply_ackermann_control(&mut self, msg: Control, time_delta: TimeDelta) {
         let Control {
             lateral:
                 Lateral {
                     steering_tire_angle,
+                    steering_tire_rotation_rate,
                     is_defined_steering_tire_rotation_rate,
                     ..
                 },
@@ -196,6 +491,7 @@ impl VehiclePub {
                 Longitudinal {
                     velocity,
                     acceleration,
+                    jerk,
                     is_defined_acceleration,
                     is_defined_jerk,
                     ..
@@ -203,47 +499,126 @@ impl VehiclePub {
             ..
         } = msg;
 
-        if is_defined_steering_tire_rotation_rate {
-            static ONCE: Once = Once::new();
-            ONCE.call_once(|| {
-                log_warn!(
-                    env!("CARGO_BIN_NAME"),
-                    "`is_defined_steering_tire_rotation_rate` is enabled but it is unsupported."
-                );
-            });
-        }
-
-        if is_defined_jerk {
-            static ONCE: Once = Once::new();
-            ONCE.call_once(|| {
-                log_warn!(
-                    env!("CARGO_BIN_NAME"),
-                    "`is_defined_jerk` is enabled but it is unsupported."
-                );
-            });
-        }
+        // The requested acceleration, or `None` to let the controller
+        // accelerate up to its maximum.
+        let requested_accel = match self.longitudinal_mode {
+            LongitudinalMode::Velocity => {
+                if is_defined_acceleration && acceleration != 0.0 {
+                    static ONCE: Once = Once::new();
+                    ONCE.call_once(|| {
+                        log_warn!(
+                            env!("CARGO_BIN_NAME"),
+                            "`acceleration` is ignored in the velocity longitudinal mode."
+                        );
+                    });
+                }
+                // The controller does not expose its maximum
+                // acceleration, so there is no target to ramp toward.
+                if (is_defined_jerk && jerk != 0.0) || self.default_jerk_limit.is_some() {
+                    static ONCE: Once = Once::new();
+                    ONCE.call_once(|| {
+                        log_warn!(
+                            env!("CARGO_BIN_NAME"),
+                            "`jerk` and `default_jerk_limit` are ignored in the velocity longitudinal mode."
+                        );
+                    });
+                }
+                None
+            }
+            LongitudinalMode::Acceleration | LongitudinalMode::Both if is_defined_acceleration => {
+                Some(acceleration as f64)
+            }
+            LongitudinalMode::Acceleration | LongitudinalMode::Both => {
+                static ONCE: Once = Once::new();
+                ONCE.call_once(|| {
+                    log_warn!(
+                        env!("CARGO_BIN_NAME"),
+                        "`is_defined_acceleration` is required but it is disabled."
+                    );
+                });
+                None
+            }
+        };
 
-        let target_accel = if is_defined_acceleration {
-            acceleration
+        let elapsed_secs = time_delta.time_delta.as_secs_f64();
+        let current_speed = self.actor.velocity().norm() as f64;
+
+        // Slew the steering angle at the requested rate, capped by the
+        // physical maximum rate. A zero rate would hold the angle
+        // forever, so it counts as not defined.
+        let requested_rate = (is_defined_steering_tire_rotation_rate
+            && steering_tire_rotation_rate != 0.0)
+            .then_some(steering_tire_rotation_rate.abs() as f64);
+        let max_rate = match (requested_rate, self.max_steering_rate) {
+            (Some(requested), Some(max)) => Some(requested.min(max)),
+            (rate, None) | (None, rate) => rate,
+        };
+        let steering_angle = slew(
+            self.steering_angle,
+            steering_tire_angle as f64,
+            max_rate,
+            elapsed_secs,
+        );
+        self.steering_angle = Some(steering_angle);
+
+        // Shape the requested acceleration with the commanded jerk, or
+        // the default jerk limit if it is not defined. A zero jerk
+        // would hold the acceleration forever, so it counts as not
+        // defined.
+        let jerk_limit = if is_defined_jerk && jerk != 0.0 {
+            Some(jerk.abs() as f64)
         } else {
-            static ONCE: Once = Once::new();
-            ONCE.call_once(|| {
-                log_warn!(
-                    env!("CARGO_BIN_NAME"),
-                    "`is_defined_acceleration` is required but it is disabled."
-                );
-            });
-            0.0
+            self.default_jerk_limit
+        };
+        let accel =
+            requested_accel.map(|target| slew(self.accel, target, jerk_limit, elapsed_secs));
+        self.accel = accel;
+
+        // The controller reads a zero target acceleration as its
+        // maximum, so the shaped acceleration is kept off zero.
+        let controller_accel = match accel {
+            Some(accel) => accel.abs().max(MIN_CONTROLLER_ACCEL).copysign(accel),
+            None => 0.0,
+        };
+
+        // Without a target velocity, track the speed reached by the
+        // acceleration within the tick, so that the speed control of
+        // the controller agrees with the acceleration. The vehicle
+        // travels forward in this mode unless the reverse gear is
+        // engaged, and it stops rather than turns around when it
+        // moves against the direction of travel.
+        let target_speed = match self.longitudinal_mode {
+            LongitudinalMode::Velocity | LongitudinalMode::Both => velocity as f64,
+            LongitudinalMode::Acceleration => {
+                let direction = match self.gear {
+                    Some(GearType::REVERSE | GearType::REVERSE_2) => -1.0,
+                    _ => 1.0,
+                };
+                let transform = coord::isometry(&self.actor.transform());
+                let actor_velocity = coord::vector(&self.actor.velocity());
+                let forward_speed = transform
+                    .rotation
+                    .inverse_transform_vector(&actor_velocity)
+                    .x as f64;
+                let speed = direction * forward_speed + accel.unwrap_or(0.0) * elapsed_secs;
+                direction * speed.max(0.0)
+            }
+        };
+
+        // The controller reverses for negative speeds. The gear
+        // command decides the direction if it is given.
+        let target_speed = match self.gear {
+            Some(GearType::REVERSE | GearType::REVERSE_2) => -target_speed.abs(),
+            Some(GearType::NONE) | None => target_speed,
+            Some(_) => target_speed.abs(),
         };
 
         self.controller.set_target(TargetRequest {
-            steering_angle: steering_tire_angle as f64,
-            speed: velocity as f64,
-            accel: target_accel as f64,
+            steering_angle,
+            speed: target_speed,
+            accel: controller_accel,
         });
 
-        let elapsed_secs = time_delta.time_delta.as_secs_f64();
-        let current_speed = self.actor.velocity().norm();
         let (_, pitch_radians, _) = self.actor.transform().rotation.euler_angles();
 
         let (
@@ -257,17 +632,81 @@ impl VehiclePub {
             _,
         ) = self
             .controller
-            .step(elapsed_secs, current_speed as f64, pitch_radians as f64);
+            .step(elapsed_secs, current_speed, pitch_radians as f64);
 
-        self.actor.apply_control(&VehicleControl {
-            throttle: throttle as f32,
-            steer: steer as f32,
-            brake: brake as f32,
-            hand_brake,
-            reverse,
-            manual_gear_shift: false,
-            gear: 0,
-        });
+        self.apply_control(
+            VehicleControl {
+                throttle: throttle as f32,
+                steer: steer as f32,
+                brake: brake as f32,
+                hand_brake,
+                reverse,
+                manual_gear_shift: false,
+                gear: 0,
+            },
+            self.gear,
+        );
+    }
+
+    /// Applies a control with the gear command overriding its gear
+    /// related fields.
+    fn apply_control(&mut self, mut control: VehicleControl, gear: Option<GearType>) {
+        let gear = gear.filter(|&gear| gear != GearType::NONE);
+        if let Some(gear) = gear {
+            apply_gear(&mut control, gear);
+        }
+        self.actor.apply_control(&control);
+        self.applied_control = Some(control);
+        self.applied_gear = gear;
+    }
+}
+
+/// Overrides the gear related fields of a control by the gear
+/// command.
+fn apply_gear(control: &mut VehicleControl, gear: GearType) {
+    use GearType as G;
+
+    match gear {
+        G::NONE => {}
+        // Carla's manual gear 0 is the neutral.
+        G::NEUTRAL => {
+            control.reverse = false;
+            control.manual_gear_shift = true;
+            control.gear = 0;
+        }
+        G::REVERSE | G::REVERSE_2 => {
+            control.reverse = true;
+            control.manual_gear_shift = false;
+        }
+        // Hold the vehicle with the hand brake.
+        G::PARK => {
+            control.throttle = 0.0;
+            control.hand_brake = true;
+            control.reverse = false;
+            control.manual_gear_shift = false;
+        }
+        G::LOW | G::LOW_2 => {
+            control.reverse = false;
+            control.manual_gear_shift = true;
+            control.gear = 1;
+        }
+        _ => {
+            control.reverse = false;
+            control.manual_gear_shift = false;
+        }
+    }
+}
+
+/// Moves a value toward the target by at most `max_rate` per second
+/// over the elapsed time. The target is reached at once if the rate
+/// is unlimited or the current value is unknown.
+fn slew(current: Option<f64>, target: f64, max_rate: Option<f64>, elapsed_secs: f64) -> f64 {
+    match (current, max_rate) {
+        (Some(current), Some(max_rate)) => {
+            let max_step = max_rate * elapsed_secs;
+            current + (target - current).clamp(-max_step, max_step)
+        }
+        _ => target,
     }
 }
 
@@ -306,3 +745,23 @@ async fn forward_control_cmd(
         }
     }
 }
+
+async fn forward_gear_cmd(
+    mut gear_stream: impl Stream<Item = GearCommand> + Unpin,
+    gear_tx: watch::Sender<Option<GearType>>,
+) {
+    while let Some(msg) = gear_stream.next().await {
+        let Some(gear) = GearType::from_u8(msg.command) else {
+            log_warn!(
+                env!("CARGO_BIN_NAME"),
+                "Unsupported gear command {}",
+                msg.command
+            );
+            continue;
+        };
+        let ok = gear_tx.send(Some(gear)).is_ok();
+        if !ok {
+            break;
+        }
+    }
+}
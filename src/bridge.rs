@@ -1,16 +1,123 @@
 use crate::qos;
 use anyhow::Result;
-use r2r::{std_msgs::msg::Empty, Node, Publisher};
+use carla::rpc::ActorId;
+use r2r::{
+    builtin_interfaces::msg::Time,
+    diagnostic_msgs::msg::{DiagnosticArray, DiagnosticStatus},
+    geometry_msgs::msg::TransformStamped,
+    log_error,
+    std_msgs::msg::{Empty, Header},
+    tf2_msgs::msg::TFMessage,
+    Node, Publisher,
+};
+use std::collections::{BTreeMap, HashSet};
 
 /// Serves ROS topics about the simulation runtime.
 pub struct Bridge {
     pub tick: Publisher<Empty>,
+    tf: Publisher<TFMessage>,
+    tf_static: Publisher<TFMessage>,
+    /// All static transforms keyed by the child frame ID, along with
+    /// the ID of the actor owning them. They are sent as a whole since
+    /// late subscribers of `/tf_static` only receive the last message.
+    static_transforms: BTreeMap<String, (ActorId, TransformStamped)>,
+    /// Whether `static_transforms` changed since it was last sent.
+    is_static_changed: bool,
+    diagnostics: Publisher<DiagnosticArray>,
 }
 
 impl Bridge {
     pub fn new(node: &mut Node) -> Result<Self> {
         Ok(Self {
             tick: node.create_publisher("tick", qos::best_effort())?,
+            tf: node.create_publisher("/tf", qos::reliable())?,
+            tf_static: node.create_publisher("/tf_static", qos::static_tf())?,
+            static_transforms: BTreeMap::new(),
+            is_static_changed: false,
+            diagnostics: node.create_publisher("/diagnostics", qos::reliable())?,
         })
     }
+
+    /// Drops the static transforms of removed actors. The remaining
+    /// ones are sent again with the next tick messages.
+    pub fn remove_actors(&mut self, ids: &HashSet<ActorId>) {
+        let len = self.static_transforms.len();
+        self.static_transforms
+            .retain(|_, (actor_id, _)| !ids.contains(actor_id));
+        if self.static_transforms.len() != len {
+            self.is_static_changed = true;
+        }
+    }
+
+    /// Publishes the messages collected from actors in a tick.
+    ///
+    /// Failures are logged rather than returned so that they do not
+    /// stop the bridge.
+    pub fn publish_tick_messages(&mut self, time: &Time, messages: TickMessages) {
+        let TickMessages {
+            transforms,
+            static_transforms,
+            diagnostics,
+        } = messages;
+
+        if !transforms.is_empty() {
+            if let Err(error) = self.tf.publish(&TFMessage { transforms }) {
+                log_error!(env!("CARGO_BIN_NAME"), "Unable to publish /tf: {error}");
+            }
+        }
+
+        if !static_transforms.is_empty() {
+            self.static_transforms.extend(
+                static_transforms
+                    .into_iter()
+                    .map(|(id, transform)| (transform.child_frame_id.clone(), (id, transform))),
+            );
+            self.is_static_changed = true;
+        }
+
+        if self.is_static_changed {
+            self.is_static_changed = false;
+            let msg = TFMessage {
+                transforms: self
+                    .static_transforms
+                    .values()
+                    .map(|(_, transform)| transform.clone())
+                    .collect(),
+            };
+            if let Err(error) = self.tf_static.publish(&msg) {
+                log_error!(
+                    env!("CARGO_BIN_NAME"),
+                    "Unable to publish /tf_static: {error}"
+                );
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            let msg = DiagnosticArray {
+                header: Header {
+                    stamp: time.clone(),
+                    frame_id: "".to_string(),
+                },
+                status: diagnostics,
+            };
+            if let Err(error) = self.diagnostics.publish(&msg) {
+                log_error!(
+                    env!("CARGO_BIN_NAME"),
+                    "Unable to publish diagnostics: {error}"
+                );
+            }
+        }
+    }
+}
+
+/// Messages collected from actors and published together once per
+/// tick.
+#[derive(Debug, Default)]
+pub struct TickMessages {
+    /// Transforms broadcast on `/tf`.
+    pub transforms: Vec<TransformStamped>,
+    /// Transforms added to `/tf_static` along with the ID of the actor
+    /// owning them.
+    pub static_transforms: Vec<(ActorId, TransformStamped)>,
+    pub diagnostics: Vec<DiagnosticStatus>,
 }
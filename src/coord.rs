@@ -0,0 +1,184 @@
+//! Conversions from Carla's coordinate system to ROS conventions.
+//!
+//! Carla follows Unreal Engine's left-handed system with x forward, y
+//! right and z up, while ROS uses the right-handed system with x
+//! forward, y left and z up defined by REP-103. The conversion mirrors
+//! the y axis. Any data read from Carla must go through these
+//! functions before it is published.
+
+use nalgebra as na;
+
+/// Converts a position or a vector quantity, such as velocity,
+/// acceleration and force.
+pub fn vector(vec: &na::Vector3<f32>) -> na::Vector3<f32> {
+    na::Vector3::new(vec.x, -vec.y, vec.z)
+}
+
+/// Converts an axial vector quantity, such as angular velocity.
+///
+/// Unlike ordinary vectors, axial vectors flip the components
+/// orthogonal to the mirrored axis.
+pub fn axial_vector(vec: &na::Vector3<f32>) -> na::Vector3<f32> {
+    na::Vector3::new(-vec.x, vec.y, -vec.z)
+}
+
+/// Converts an angular velocity in degrees per second to radians per
+/// second, as reported by actors in Carla.
+pub fn angular_velocity_degrees(vec: &na::Vector3<f32>) -> na::Vector3<f32> {
+    axial_vector(&vec.map(f32::to_radians))
+}
+
+/// Converts a rotation built from Carla roll, pitch and yaw angles.
+///
+/// In Unreal Engine, positive pitch raises the nose and positive yaw
+/// turns clockwise when looking from above. Both of them are reversed
+/// in ROS, while roll keeps the same direction.
+pub fn rotation(rot: &na::UnitQuaternion<f32>) -> na::UnitQuaternion<f32> {
+    let (roll, pitch, yaw) = rot.euler_angles();
+    na::UnitQuaternion::from_euler_angles(roll, -pitch, -yaw)
+}
+
+/// Converts a transform consisting of a location and a rotation.
+pub fn isometry(iso: &na::Isometry3<f32>) -> na::Isometry3<f32> {
+    na::Isometry3::from_parts(
+        vector(&iso.translation.vector).into(),
+        rotation(&iso.rotation),
+    )
+}
+
+/// Converts a point of a lidar or radar point cloud.
+pub fn point([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [x, -y, z]
+}
+
+/// Converts points serialized in native-endian float32 in place,
+/// where the y coordinate is at `y_offset` bytes of each point.
+///
+/// Negating a float flips its sign bit, so the bytes are modified
+/// without decoding the values.
+pub fn points_in_place(data: &mut [u8], point_step: usize, y_offset: usize) {
+    let sign_byte = if cfg!(target_endian = "big") { 0 } else { 3 };
+    for point in data.chunks_exact_mut(point_step) {
+        point[y_offset + sign_byte] ^= 0x80;
+    }
+}
+
+/// Converts an azimuth angle in radians measured around the z axis.
+pub fn azimuth(azimuth: f32) -> f32 {
+    -azimuth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_6};
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_vector_eq(lhs: &na::Vector3<f32>, rhs: &na::Vector3<f32>) {
+        assert!((lhs - rhs).norm() < EPSILON, "{lhs:?} != {rhs:?}");
+    }
+
+    fn assert_rotation_eq(lhs: &na::UnitQuaternion<f32>, rhs: &na::UnitQuaternion<f32>) {
+        assert!(lhs.angle_to(rhs) < EPSILON, "{lhs:?} != {rhs:?}");
+    }
+
+    #[test]
+    fn vector_mirrors_y() {
+        let vec = vector(&na::Vector3::new(1.0, 2.0, 3.0));
+        assert_vector_eq(&vec, &na::Vector3::new(1.0, -2.0, 3.0));
+    }
+
+    #[test]
+    fn compass_north_maps_to_positive_y() {
+        // Carla's north points to -y.
+        let north = vector(&na::Vector3::new(0.0, -1.0, 0.0));
+        assert_vector_eq(&north, &na::Vector3::y());
+    }
+
+    #[test]
+    fn axial_vector_follows_cross_product() {
+        let lhs = na::Vector3::new(1.0, 2.0, 3.0);
+        let rhs = na::Vector3::new(-4.0, 0.5, 2.0);
+        assert_vector_eq(
+            &axial_vector(&lhs.cross(&rhs)),
+            &vector(&lhs).cross(&vector(&rhs)),
+        );
+        assert_vector_eq(
+            &axial_vector(&na::Vector3::new(1.0, 2.0, 3.0)),
+            &na::Vector3::new(-1.0, 2.0, -3.0),
+        );
+    }
+
+    #[test]
+    fn rotation_reverses_yaw() {
+        let carla = na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2);
+        let (roll, pitch, yaw) = rotation(&carla).euler_angles();
+        assert!(roll.abs() < EPSILON);
+        assert!(pitch.abs() < EPSILON);
+        assert!((yaw + FRAC_PI_2).abs() < EPSILON);
+
+        // Turning clockwise from above faces the right side, which is
+        // -y in ROS.
+        let forward = rotation(&carla) * na::Vector3::x();
+        assert_vector_eq(&forward, &-na::Vector3::y());
+    }
+
+    #[test]
+    fn rotation_reverses_pitch_and_keeps_roll() {
+        let carla = na::UnitQuaternion::from_euler_angles(FRAC_PI_6, FRAC_PI_6, 0.0);
+        let (roll, pitch, yaw) = rotation(&carla).euler_angles();
+        assert!((roll - FRAC_PI_6).abs() < EPSILON);
+        assert!((pitch + FRAC_PI_6).abs() < EPSILON);
+        assert!(yaw.abs() < EPSILON);
+
+        // Raising the nose points the forward axis upward.
+        let carla = na::UnitQuaternion::from_euler_angles(0.0, FRAC_PI_6, 0.0);
+        let forward = rotation(&carla) * na::Vector3::x();
+        assert!(forward.z > 0.0);
+    }
+
+    #[test]
+    fn isometry_round_trips() {
+        let carla = na::Isometry3::from_parts(
+            na::Translation3::new(1.0, 2.0, 3.0),
+            na::UnitQuaternion::from_euler_angles(0.1, -0.2, 0.3),
+        );
+        let ros = isometry(&carla);
+        assert_vector_eq(&ros.translation.vector, &na::Vector3::new(1.0, -2.0, 3.0));
+        assert_rotation_eq(&ros.rotation, &rotation(&carla.rotation));
+
+        let back = isometry(&ros);
+        assert_vector_eq(&back.translation.vector, &carla.translation.vector);
+        assert_rotation_eq(&back.rotation, &carla.rotation);
+    }
+
+    #[test]
+    fn point_mirrors_y() {
+        assert_eq!(point([1.0, 2.0, 3.0]), [1.0, -2.0, 3.0]);
+    }
+
+    #[test]
+    fn points_in_place_mirrors_y() {
+        let points = [[1.0f32, 2.0, 3.0, 4.0], [-5.0, -6.0, 7.0, 8.0]];
+        let mut data: Vec<u8> = points
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        points_in_place(&mut data, 16, 4);
+
+        let values: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(values, [1.0, -2.0, 3.0, 4.0, -5.0, 6.0, 7.0, 8.0]);
+    }
+
+    #[test]
+    fn azimuth_agrees_with_point() {
+        let [x, y, _] = [3.0, 4.0, 0.0];
+        let [ros_x, ros_y, _] = point([x, y, 0.0]);
+        assert!((azimuth(y.atan2(x)) - ros_y.atan2(ros_x)).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,363 @@
+use carla::sensor::data::{LidarDetection, SemanticLidarDetection};
+use r2r::{log_warn, std_msgs::msg::Header};
+use std::{borrow::Cow, f32::consts::TAU, mem};
+
+/// The tolerance of rotated angles in radians for rounding errors in
+/// timestamps.
+const ANGLE_TOLERANCE: f32 = 1e-4;
+
+/// A lidar point with a known horizontal direction.
+pub trait LidarRecord: Clone {
+    /// The azimuth in radians in Carla's frame, which increases as
+    /// the lidar spins.
+    fn azimuth(&self) -> f32;
+}
+
+impl LidarRecord for LidarDetection {
+    fn azimuth(&self) -> f32 {
+        self.point.y.atan2(self.point.x)
+    }
+}
+
+impl LidarRecord for SemanticLidarDetection {
+    fn azimuth(&self) -> f32 {
+        self.point.y.atan2(self.point.x)
+    }
+}
+
+/// A run of consecutive points scanned within one tick.
+#[derive(Debug, Clone, Copy)]
+pub struct SweepSlice {
+    /// The simulation time in seconds when the last ray of the slice
+    /// was scanned.
+    pub end_time: f64,
+    /// The number of points in the slice.
+    pub len: usize,
+}
+
+/// A full lidar sweep assembled from slices.
+#[derive(Debug)]
+pub struct Sweep<'a, T>
+where
+    T: Clone,
+{
+    /// The header of the first slice in the sweep.
+    pub header: Header,
+    /// The simulation time of the header in seconds.
+    pub timestamp: f64,
+    pub points: Cow<'a, [T]>,
+    /// The slices making up the points in the scanning order.
+    pub slices: Vec<SweepSlice>,
+}
+
+/// Assembles partial lidar slices into full 360° sweeps.
+///
+/// Carla delivers the portion of a lidar rotation simulated within
+/// one tick. The rotated angle of a slice is derived from the
+/// rotation frequency and the time elapsed since the previous slice.
+/// The slice completing a revolution is split at the azimuth where
+/// the sweep started, and the remaining points begin the next sweep.
+/// A slice covering more than one revolution cannot be split by
+/// azimuth, so it is returned alone as a sweep.
+#[derive(Debug)]
+pub struct SweepBuffer<T> {
+    rotation_frequency: f64,
+    prev_timestamp: Option<f64>,
+    /// The azimuth of the last scanned ray, after which the next
+    /// slice starts.
+    next_azimuth: Option<f32>,
+    /// The accumulated rotation of the current sweep in revolutions.
+    revolutions: f64,
+    header: Option<(Header, f64)>,
+    points: Vec<T>,
+    slices: Vec<SweepSlice>,
+    /// Whether a slice covering more than one revolution was warned
+    /// about.
+    is_overfull_warned: bool,
+}
+
+impl<T> SweepBuffer<T>
+where
+    T: LidarRecord,
+{
+    pub fn new(rotation_frequency: f64) -> Self {
+        Self {
+            rotation_frequency,
+            prev_timestamp: None,
+            next_azimuth: None,
+            revolutions: 0.0,
+            header: None,
+            points: vec![],
+            slices: vec![],
+            is_overfull_warned: false,
+        }
+    }
+
+    /// Adds a slice of points and returns the assembled sweep once a
+    /// full revolution is completed.
+    pub fn push(
+        &mut self,
+        header: Header,
+        timestamp: f64,
+        slice: &[T],
+    ) -> Option<Sweep<'static, T>> {
+        let prev_timestamp = self.prev_timestamp.replace(timestamp);
+
+        // The rotated angle of the first slice is unknown, while its
+        // last point tells where the next slice starts. Start over if
+        // the simulation time goes backwards.
+        let (elapsed, start_azimuth) = match (prev_timestamp, self.next_azimuth) {
+            (Some(prev), Some(start_azimuth)) if prev <= timestamp => {
+                (timestamp - prev, start_azimuth)
+            }
+            _ => {
+                self.revolutions = 0.0;
+                self.header = None;
+                self.points.clear();
+                self.slices.clear();
+                self.next_azimuth = slice.last().map(T::azimuth);
+                return None;
+            }
+        };
+        let revolutions = elapsed * self.rotation_frequency;
+        let span = (revolutions * TAU as f64) as f32;
+        self.next_azimuth = Some((start_azimuth + span).rem_euclid(TAU));
+
+        // The points of an over-full slice overlap in azimuth. The
+        // slice is returned as is, and the partial sweep before it is
+        // dropped.
+        if span > TAU + ANGLE_TOLERANCE {
+            if !self.is_overfull_warned {
+                self.is_overfull_warned = true;
+                log_warn!(
+                    env!("CARGO_BIN_NAME"),
+                    "A lidar tick covers {revolutions:.2} revolutions, so slices are published as sweeps without assembly"
+                );
+            }
+            self.revolutions = 0.0;
+            self.header = None;
+            self.points.clear();
+            self.slices.clear();
+            return Some(Sweep {
+                header,
+                timestamp,
+                points: Cow::Owned(slice.to_vec()),
+                slices: vec![SweepSlice {
+                    end_time: timestamp,
+                    len: slice.len(),
+                }],
+            });
+        }
+
+        self.header
+            .get_or_insert_with(|| (header.clone(), timestamp));
+
+        let remaining = TAU - (self.revolutions * TAU as f64) as f32;
+        if span < remaining - ANGLE_TOLERANCE {
+            self.points.extend_from_slice(slice);
+            self.slices.push(SweepSlice {
+                end_time: timestamp,
+                len: slice.len(),
+            });
+            self.revolutions += revolutions;
+            return None;
+        }
+
+        // Points scanned after the revolution is completed go to the
+        // next sweep. The ray landing on the start azimuth closes the
+        // sweep, and a point slightly behind the slice start belongs
+        // to the current sweep.
+        let wrap_threshold = (span + TAU) / 2.0;
+        let prev_len = self.points.len();
+        let mut overflow = vec![];
+        for point in slice {
+            let rotated = (point.azimuth() - start_azimuth).rem_euclid(TAU);
+            if rotated < remaining + ANGLE_TOLERANCE || rotated > wrap_threshold {
+                self.points.push(point.clone());
+            } else {
+                overflow.push(point.clone());
+            }
+        }
+
+        // The revolution is completed partway through the slice.
+        let completed = if span > 0.0 {
+            (remaining / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let split_time = timestamp - elapsed * (1.0 - completed) as f64;
+        if self.points.len() > prev_len {
+            self.slices.push(SweepSlice {
+                end_time: split_time,
+                len: self.points.len() - prev_len,
+            });
+        }
+
+        let (sweep_header, sweep_timestamp) = self.header.take()?;
+        let sweep_points = mem::replace(&mut self.points, overflow);
+        let sweep_slices = mem::take(&mut self.slices);
+        self.revolutions = ((span - remaining).max(0.0) / TAU) as f64;
+        if !self.points.is_empty() {
+            self.header = Some((header, timestamp));
+            self.slices.push(SweepSlice {
+                end_time: timestamp,
+                len: self.points.len(),
+            });
+        }
+        Some(Sweep {
+            header: sweep_header,
+            timestamp: sweep_timestamp,
+            points: Cow::Owned(sweep_points),
+            slices: sweep_slices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const EPSILON: f64 = 1e-6;
+
+    /// A ray at an azimuth.
+    #[derive(Debug, Clone)]
+    struct Ray(f32);
+
+    impl LidarRecord for Ray {
+        fn azimuth(&self) -> f32 {
+            self.0
+        }
+    }
+
+    /// Generates evenly spaced rays turning `span` radians after the
+    /// `start` azimuth, excluding the start and including the end.
+    fn rays(start: f32, span: f32, len: usize) -> Vec<Ray> {
+        (1..=len)
+            .map(|index| {
+                let azimuth = start + span * index as f32 / len as f32;
+                Ray((azimuth + PI).rem_euclid(TAU) - PI)
+            })
+            .collect()
+    }
+
+    /// Creates a buffer spinning at 10 Hz whose next slice starts at
+    /// zero azimuth at 1 s.
+    fn buffer() -> SweepBuffer<Ray> {
+        let mut buffer = SweepBuffer::new(10.0);
+        assert!(buffer
+            .push(Header::default(), 1.0, &rays(-PI / 2.0, PI / 2.0, 10))
+            .is_none());
+        buffer
+    }
+
+    #[test]
+    fn exact_revolution_is_one_sweep() {
+        let mut buffer = buffer();
+
+        for tick in 1..=3 {
+            let timestamp = 1.0 + 0.1 * tick as f64;
+            let sweep = buffer
+                .push(Header::default(), timestamp, &rays(0.0, TAU, 100))
+                .unwrap();
+            assert_eq!(sweep.points.len(), 100);
+            assert!((sweep.timestamp - timestamp).abs() < EPSILON);
+            assert_eq!(sweep.slices.len(), 1);
+            assert!((sweep.slices[0].end_time - timestamp).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn revolution_is_split_mid_slice() {
+        let mut buffer = buffer();
+
+        // Each tick rotates 0.4 revolutions, so the third slice
+        // completes the revolution halfway.
+        let span = 0.4 * TAU;
+        assert!(buffer
+            .push(Header::default(), 1.04, &rays(0.0, span, 40))
+            .is_none());
+        assert!(buffer
+            .push(Header::default(), 1.08, &rays(span, span, 40))
+            .is_none());
+        let sweep = buffer
+            .push(Header::default(), 1.12, &rays(2.0 * span, span, 40))
+            .unwrap();
+
+        assert_eq!(sweep.points.len(), 100);
+        assert!((sweep.timestamp - 1.04).abs() < EPSILON);
+        let ends: Vec<_> = sweep.slices.iter().map(|slice| slice.end_time).collect();
+        let lens: Vec<_> = sweep.slices.iter().map(|slice| slice.len).collect();
+        assert_eq!(lens, [40, 40, 20]);
+        assert!((ends[2] - 1.10).abs() < EPSILON, "{ends:?}");
+
+        // The rest of the slice begins the next sweep.
+        assert!(buffer
+            .push(Header::default(), 1.16, &rays(3.0 * span, span, 40))
+            .is_none());
+        let sweep = buffer
+            .push(Header::default(), 1.20, &rays(4.0 * span, span, 40))
+            .unwrap();
+        assert_eq!(sweep.points.len(), 100);
+        assert!((sweep.timestamp - 1.12).abs() < EPSILON);
+        let lens: Vec<_> = sweep.slices.iter().map(|slice| slice.len).collect();
+        assert_eq!(lens, [20, 40, 40]);
+    }
+
+    #[test]
+    fn backward_time_starts_over() {
+        let mut buffer = buffer();
+        let span = 0.4 * TAU;
+        assert!(buffer
+            .push(Header::default(), 1.04, &rays(0.0, span, 40))
+            .is_none());
+
+        // The partial sweep is dropped, and the slice only tells where
+        // the next one starts.
+        assert!(buffer
+            .push(Header::default(), 0.5, &rays(span, span, 40))
+            .is_none());
+        assert!(buffer
+            .push(Header::default(), 0.54, &rays(2.0 * span, span, 40))
+            .is_none());
+        assert!(buffer
+            .push(Header::default(), 0.58, &rays(3.0 * span, span, 40))
+            .is_none());
+        let sweep = buffer
+            .push(Header::default(), 0.62, &rays(4.0 * span, span, 40))
+            .unwrap();
+        assert_eq!(sweep.points.len(), 100);
+        assert!((sweep.timestamp - 0.54).abs() < EPSILON);
+    }
+
+    #[test]
+    fn overfull_slice_is_one_sweep() {
+        let mut buffer = buffer();
+
+        // Each tick rotates 2.5 revolutions.
+        for tick in 1..=3 {
+            let timestamp = 1.0 + 0.25 * tick as f64;
+            let start = (tick as f32 * 2.5 - 2.5) * TAU;
+            let sweep = buffer
+                .push(Header::default(), timestamp, &rays(start, 2.5 * TAU, 250))
+                .unwrap();
+            assert_eq!(sweep.points.len(), 250);
+            assert!((sweep.timestamp - timestamp).abs() < EPSILON);
+        }
+
+        // Assembly resumes when the ticks become shorter.
+        let start = 7.5 * TAU;
+        assert!(buffer
+            .push(Header::default(), 1.8, &rays(start, 0.5 * TAU, 50))
+            .is_none());
+        let sweep = buffer
+            .push(
+                Header::default(),
+                1.85,
+                &rays(start + 0.5 * TAU, 0.5 * TAU, 50),
+            )
+            .unwrap();
+        assert_eq!(sweep.points.len(), 100);
+        assert!((sweep.timestamp - 1.8).abs() < EPSILON);
+    }
+}
@@ -1,15 +1,19 @@
 mod actor_node;
 mod bridge;
+mod coord;
+mod lidar_sweep;
 mod map_srv;
+mod noise;
 mod params;
 mod qos;
 mod time;
 mod types;
 mod utils;
+mod worker_pool;
 
 use actor_node::generic::{ActorPub, ActorSub};
 use anyhow::Result;
-use bridge::Bridge;
+use bridge::{Bridge, TickMessages};
 use carla::{
     client::{Client, World},
     prelude::*,
@@ -18,14 +22,16 @@ use carla::{
 use futures::{future::BoxFuture, join, select, stream::FuturesUnordered, FutureExt, StreamExt};
 use itertools::Itertools;
 use params::Params;
-use r2r::{log_info, std_msgs::msg::Empty, Clock, ClockType, Context, Node};
+use r2r::{log_info, log_warn, std_msgs::msg::Empty, Clock, ClockType, Context, Node};
 use std::{
     collections::{HashMap, HashSet},
     future::IntoFuture,
     time::Duration,
 };
-use time::TimeBuffer;
+use time::{SimClock, TimeBuffer};
 use tokio::{spawn, task::spawn_blocking};
+use types::MapProjection;
+use worker_pool::WorkerPool;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,20 +41,24 @@ async fn main() -> Result<()> {
     // Construct publishers, subscribers and services, etc.
     let ctx = Context::create()?;
     let mut node = Node::create(ctx, "carla_autoware_bridge", "/carla")?;
+    let params = Params::load(&node)?;
     let Params {
-        carla_host,
+        ref carla_host,
         carla_port,
         carla_timeout_millis,
-    } = Params::load(&node)?;
+        map_projection,
+        ..
+    } = params;
 
     // Create Carla client
-    let mut client = Client::connect(&carla_host, carla_port, None);
+    let mut client = Client::connect(carla_host, carla_port, None);
     client.set_timeout(Duration::from_millis(carla_timeout_millis));
 
-    let map_srv = map_srv::new(&mut node, client.world())?.map(|result| result.unwrap());
+    let map_srv =
+        map_srv::new(&mut node, client.world(), map_projection)?.map(|result| result.unwrap());
 
     let looper = async {
-        spawn_blocking(move || looper(node, client, future_tx))
+        spawn_blocking(move || looper(node, client, params, future_tx))
             .await
             .unwrap()
             .unwrap();
@@ -89,33 +99,47 @@ async fn waiter(future_rx: flume::Receiver<BoxFuture<'static, ()>>) {
 fn looper(
     mut node: Node,
     client: Client,
+    params: Params,
     future_tx: flume::Sender<BoxFuture<'static, ()>>,
 ) -> Result<()> {
     let world = client.world();
 
     let mut publishers: HashMap<ActorId, _> = HashMap::new();
-    let bridge = Bridge::new(&mut node)?;
+    let mut bridge = Bridge::new(&mut node)?;
     let mut clock = Clock::create(ClockType::RosTime)?;
     let mut time_buffer = TimeBuffer::default();
+    let sim_clock = SimClock::default();
+    let worker_pool = WorkerPool::new(params.sensor_workers);
     let mut world_id = world.id();
 
     loop {
         node.spin_once(Duration::from_millis(10));
         let snapshot = world.wait_for_tick();
+        let sim_secs = snapshot.timestamp().elapsed_seconds;
 
-        // Reset time buffer if world ID changed.
+        // Reset time buffer and simulation clock if world ID changed.
         {
             let curr_world_id = snapshot.id();
             if world_id != curr_world_id {
                 time_buffer = TimeBuffer::default();
+                sim_clock.reanchor(sim_secs, clock.get_now()?);
             }
             world_id = curr_world_id;
         }
 
+        sim_clock.anchor(sim_secs, clock.get_now()?);
         let time_delta = time_buffer.step(snapshot.timestamp());
 
         // Update actors
-        let new_subs = update_actors(&mut node, &world, &mut publishers)?;
+        let new_subs = update_actors(
+            &mut node,
+            &world,
+            &params,
+            &sim_clock,
+            &worker_pool,
+            &mut publishers,
+            &mut bridge,
+        )?;
         let is_all_sent = new_subs.into_iter().all(|sub| {
             let future = spawn(sub.into_future())
                 .map(|result| result.unwrap())
@@ -128,11 +152,13 @@ fn looper(
         }
 
         // Poll actor publishers
-        let time = Clock::to_builtin_time(&clock.get_now()?);
+        let time = sim_clock.stamp(sim_secs).unwrap();
+        let mut tick_messages = TickMessages::default();
         publishers.iter_mut().try_for_each(|(_id, pub_)| {
-            pub_.poll(&time, time_delta.clone())?;
+            pub_.poll(&time, time_delta.clone(), &mut tick_messages)?;
             anyhow::Ok(())
         })?;
+        bridge.publish_tick_messages(&time, tick_messages);
 
         // Publish tick
         bridge.tick.publish(&Empty {})?;
@@ -144,7 +170,11 @@ fn looper(
 fn update_actors(
     node: &mut Node,
     world: &World,
+    params: &Params,
+    sim_clock: &SimClock,
+    worker_pool: &WorkerPool,
     publishers: &mut HashMap<ActorId, ActorPub>,
+    bridge: &mut Bridge,
 ) -> Result<Vec<ActorSub>> {
     // List actors in the simulator
     let mut actors: HashMap<ActorId, _> = world
@@ -170,18 +200,52 @@ fn update_actors(
         log_info!(env!("CARGO_BIN_NAME"), "Removing actors: {del_keys:?}");
     }
 
+    // Instance segmentation cameras encode actor IDs in 16 bits, so
+    // larger IDs are truncated and may collide with other actors.
+    let has_instance_camera = actors
+        .values()
+        .any(|actor| actor.type_id() == "sensor.camera.instance_segmentation");
+    if has_instance_camera {
+        let truncated: Vec<_> = new_keys
+            .iter()
+            .filter(|&&id| id > u16::MAX as ActorId)
+            .collect();
+        if !truncated.is_empty() {
+            log_warn!(
+                env!("CARGO_BIN_NAME"),
+                "Actor IDs {truncated:?} exceed 16 bits and do not match instance IDs of instance segmentation cameras"
+            );
+        }
+    }
+
+    // GNSS sensors compute coordinates around the OpenDRIVE
+    // geo-reference, which the UTM map projection ignores.
+    if params.map_projection == MapProjection::Utm {
+        let gnss: Vec<_> = new_keys
+            .iter()
+            .filter(|id| actors[id].type_id() == "sensor.other.gnss")
+            .collect();
+        if !gnss.is_empty() {
+            log_warn!(
+                env!("CARGO_BIN_NAME"),
+                "GNSS sensors {gnss:?} disagree with the map projected in UTM, set map_projection to 'geo_reference' to match them"
+            );
+        }
+    }
+
     // Inert new actors
     let subs: Vec<_> = new_keys
         .into_iter()
         .map(|id| {
             let actor = actors.remove(&id).unwrap();
-            let (pub_, sub) = actor_node::new(node, actor)?;
+            let (pub_, sub) = actor_node::new(node, actor, params, sim_clock, worker_pool)?;
             publishers.insert(id, pub_);
             anyhow::Ok(sub)
         })
         .try_collect()?;
 
     // Remove vanishing actors
+    bridge.remove_actors(&del_keys);
     del_keys.into_iter().for_each(|id| {
         publishers.remove(&id).unwrap();
     });
@@ -1,3 +1,4 @@
+use crate::types::MapProjection;
 use anyhow::{bail, Context, Result};
 use carla::client::World;
 use futures::{Future, Stream, StreamExt};
@@ -16,9 +17,13 @@ use std::{
 use tempfile::TempDir;
 use tokio::time::sleep;
 
-pub fn new(node: &mut Node, world: World) -> Result<impl Future<Output = Result<()>>> {
+pub fn new(
+    node: &mut Node,
+    world: World,
+    projection: MapProjection,
+) -> Result<impl Future<Output = Result<()>>> {
     let stream = node.create_service::<HADMapService::Service>("map")?;
-    let srv = run_service(stream.boxed(), world);
+    let srv = run_service(stream.boxed(), world, projection);
     Ok(srv)
 }
 
@@ -26,16 +31,18 @@ pub fn new(node: &mut Node, world: World) -> Result<impl Future<Output = Result<
 struct MapConverter {
     _tmpdir: TempDir,
     input_path: PathBuf,
+    projection: MapProjection,
 }
 
 impl MapConverter {
-    pub fn new() -> Result<Self> {
+    pub fn new(projection: MapProjection) -> Result<Self> {
         let tmpdir = TempDir::new()?;
         let input_path = tmpdir.path().join("input.xodr");
 
         Ok(Self {
             _tmpdir: tmpdir,
             input_path,
+            projection,
         })
     }
 
@@ -67,7 +74,7 @@ impl MapConverter {
             .arg("-o")
             .arg("/dev/stdout")
             .arg("--osm")
-            .arg("+proj=utm +zone=32 +ellps=WGS84")
+            .arg(map_projection(self.projection, opendrive))
             .output()
             .with_context(|| err(""))?;
 
@@ -81,6 +88,35 @@ impl MapConverter {
     }
 }
 
+/// Builds the projection string for Lanelet2 conversion.
+///
+/// Carla computes GNSS coordinates using only `lat_0` and `lon_0` of
+/// the `geoReference` in the OpenDRIVE header and assumes zeros if
+/// they are missing. The geo-reference projection is centered on the
+/// same origin so that the map agrees with GNSS sensors.
+fn map_projection(projection: MapProjection, opendrive: &str) -> String {
+    if projection == MapProjection::Utm {
+        return "+proj=utm +zone=32 +ellps=WGS84".to_string();
+    }
+
+    let geo_reference = opendrive
+        .split_once("<geoReference>")
+        .and_then(|(_, rest)| rest.split_once("</geoReference>"))
+        .map(|(text, _)| text)
+        .unwrap_or("");
+    let get_param = |prefix: &str| -> f64 {
+        geo_reference
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix(prefix))
+            .and_then(|value| value.trim_end_matches("]]>").parse().ok())
+            .unwrap_or(0.0)
+    };
+    let lat_0 = get_param("+lat_0=");
+    let lon_0 = get_param("+lon_0=");
+
+    format!("+proj=tmerc +lat_0={lat_0} +lon_0={lon_0} +k=1 +x_0=0 +y_0=0 +ellps=WGS84")
+}
+
 struct Cache {
     world: World,
     world_id: u64,
@@ -89,9 +125,9 @@ struct Cache {
 }
 
 impl Cache {
-    pub fn new(world: World) -> Result<Self> {
+    pub fn new(world: World, projection: MapProjection) -> Result<Self> {
         let world_id = world.id();
-        let converter = MapConverter::new()?;
+        let converter = MapConverter::new(projection)?;
         let opendrive = world.map().to_open_drive();
         let lanelet2 = converter.opendrive_to_lanelet2(&opendrive)?;
 
@@ -119,10 +155,12 @@ impl Cache {
 async fn run_service(
     mut stream: impl Stream<Item = ServiceRequest<HADMapService::Service>> + Unpin,
     world: World,
+    projection: MapProjection,
 ) -> Result<()> {
     let mut clock = Clock::create(ClockType::RosTime)?;
     let mut cache = loop {
-        let result = Cache::new(world.clone()).with_context(|| "Unable to publish world map.");
+        let result =
+            Cache::new(world.clone(), projection).with_context(|| "Unable to publish world map.");
 
         match result {
             Ok(cache) => break cache,
@@ -0,0 +1,172 @@
+//! Noise and dropout injected into published messages.
+//!
+//! Each actor owns a random generator seeded from the `noise_seed`
+//! parameter and the actor ID. The injected noise is thus
+//! reproducible regardless of the order in which sensor data arrive.
+
+use crate::params::NoiseParams;
+use carla::{geom::Location, sensor::data::LidarDetection};
+use nalgebra as na;
+use r2r::nav_msgs::msg::Odometry;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use std::borrow::Cow;
+
+pub struct Noise {
+    params: NoiseParams,
+    rng: StdRng,
+    accel_bias: na::Vector3<f32>,
+    gyro_bias: na::Vector3<f32>,
+    prev_timestamp: Option<f64>,
+}
+
+impl Noise {
+    pub fn new(params: &NoiseParams, actor_id: u32) -> Self {
+        // Spread actor IDs over the seed space to get independent
+        // random streams.
+        let seed = params.seed ^ (actor_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+        Self {
+            params: params.clone(),
+            rng: StdRng::seed_from_u64(seed),
+            accel_bias: na::Vector3::zeros(),
+            gyro_bias: na::Vector3::zeros(),
+            prev_timestamp: None,
+        }
+    }
+
+    /// Decides whether the next message is dropped.
+    pub fn drop_message(&mut self) -> bool {
+        self.bernoulli(self.params.drop_rate)
+    }
+
+    /// Applies range noise and point dropout to lidar detections.
+    ///
+    /// The range noise moves each point along its ray.
+    pub fn lidar<'a>(&mut self, slice: &'a [LidarDetection]) -> Cow<'a, [LidarDetection]> {
+        let NoiseParams {
+            lidar_range_stddev,
+            lidar_dropout_rate,
+            ..
+        } = self.params;
+
+        if lidar_range_stddev <= 0.0 && lidar_dropout_rate <= 0.0 {
+            return Cow::Borrowed(slice);
+        }
+
+        let mut points = Vec::with_capacity(slice.len());
+
+        for &LidarDetection {
+            point: Location { x, y, z },
+            intensity,
+        } in slice
+        {
+            if self.bernoulli(lidar_dropout_rate) {
+                continue;
+            }
+
+            let range = (x * x + y * y + z * z).sqrt();
+            let scale = if range > 0.0 {
+                let noisy_range = range + self.gaussian(lidar_range_stddev) as f32;
+                noisy_range.max(0.0) / range
+            } else {
+                1.0
+            };
+
+            points.push(LidarDetection {
+                point: Location {
+                    x: x * scale,
+                    y: y * scale,
+                    z: z * scale,
+                },
+                intensity,
+            });
+        }
+
+        Cow::Owned(points)
+    }
+
+    /// Adds Gaussian noise to the color values of interleaved
+    /// pixels. The fourth channel, if any, is kept intact as alpha.
+    pub fn pixels(&mut self, data: &mut [u8], channels: usize) {
+        let stddev = self.params.camera_pixel_stddev;
+        if stddev <= 0.0 {
+            return;
+        }
+
+        for pixel in data.chunks_exact_mut(channels) {
+            for value in pixel.iter_mut().take(3) {
+                let noisy = *value as f64 + self.gaussian(stddev);
+                *value = noisy.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    /// Adds white noise and random walk biases to the IMU
+    /// acceleration and angular velocity at the given simulation
+    /// time.
+    pub fn imu(
+        &mut self,
+        timestamp: f64,
+        accel: na::Vector3<f32>,
+        gyro: na::Vector3<f32>,
+    ) -> (na::Vector3<f32>, na::Vector3<f32>) {
+        let NoiseParams {
+            imu_accel_stddev,
+            imu_gyro_stddev,
+            imu_accel_bias_walk,
+            imu_gyro_bias_walk,
+            ..
+        } = self.params;
+
+        // The biases stay unchanged on the first reading or if the
+        // simulation time goes backwards.
+        let elapsed = match self.prev_timestamp.replace(timestamp) {
+            Some(prev) if prev <= timestamp => timestamp - prev,
+            _ => 0.0,
+        };
+        let sqrt_elapsed = elapsed.sqrt();
+        self.accel_bias += self.gaussian_vector(imu_accel_bias_walk * sqrt_elapsed);
+        self.gyro_bias += self.gaussian_vector(imu_gyro_bias_walk * sqrt_elapsed);
+
+        let accel = accel + self.accel_bias + self.gaussian_vector(imu_accel_stddev);
+        let gyro = gyro + self.gyro_bias + self.gaussian_vector(imu_gyro_stddev);
+        (accel, gyro)
+    }
+
+    /// Adds Gaussian noise to the position and linear velocity of an
+    /// odometry message.
+    pub fn odometry(&mut self, msg: &mut Odometry) {
+        let NoiseParams {
+            odom_position_stddev,
+            odom_velocity_stddev,
+            ..
+        } = self.params;
+
+        let position = &mut msg.pose.pose.position;
+        position.x += self.gaussian(odom_position_stddev);
+        position.y += self.gaussian(odom_position_stddev);
+        position.z += self.gaussian(odom_position_stddev);
+
+        let linear = &mut msg.twist.twist.linear;
+        linear.x += self.gaussian(odom_velocity_stddev);
+        linear.y += self.gaussian(odom_velocity_stddev);
+        linear.z += self.gaussian(odom_velocity_stddev);
+    }
+
+    fn bernoulli(&mut self, rate: f64) -> bool {
+        rate > 0.0 && self.rng.gen_bool(rate)
+    }
+
+    fn gaussian(&mut self, stddev: f64) -> f64 {
+        if stddev <= 0.0 {
+            return 0.0;
+        }
+        let sample: f64 = self.rng.sample(StandardNormal);
+        stddev * sample
+    }
+
+    fn gaussian_vector(&mut self, stddev: f64) -> na::Vector3<f32> {
+        na::Vector3::from_fn(|_, _| self.gaussian(stddev) as f32)
+    }
+}
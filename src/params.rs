@@ -1,22 +1,148 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use r2r::{log_warn, Node, ParameterValue};
 
+use crate::types::{LidarPointLayout, LongitudinalMode, MapProjection, SensorDropPolicy};
+
 const PARAM_CARLA_HOST: &str = "carla_host";
 const PARAM_CARLA_PORT: &str = "carla_port";
 const PARAM_CARLA_TIMEOUT_MILLIS: &str = "carla_timeout_millis";
+const PARAM_LIDAR_FULL_SWEEP: &str = "lidar_full_sweep";
+const PARAM_LIDAR_POINT_LAYOUT: &str = "lidar_point_layout";
+const PARAM_MAP_PROJECTION: &str = "map_projection";
+const PARAM_SENSOR_QUEUE_DEPTH: &str = "sensor_queue_depth";
+const PARAM_SENSOR_DROP_POLICY: &str = "sensor_drop_policy";
+const PARAM_SENSOR_WORKERS: &str = "sensor_workers";
+const PARAM_SENSOR_DECIMATION: &str = "sensor_decimation";
+const PARAM_MAX_STEERING_TIRE_ROTATION_RATE: &str = "max_steering_tire_rotation_rate";
+const PARAM_DEFAULT_JERK_LIMIT: &str = "default_jerk_limit";
+const PARAM_LONGITUDINAL_MODE: &str = "longitudinal_mode";
+const PARAM_CONTROL_TIMEOUT: &str = "control_timeout";
+const PARAM_NOISE_SEED: &str = "noise_seed";
+const PARAM_NOISE_DROP_RATE: &str = "noise_drop_rate";
+const PARAM_NOISE_LIDAR_RANGE_STDDEV: &str = "noise_lidar_range_stddev";
+const PARAM_NOISE_LIDAR_DROPOUT_RATE: &str = "noise_lidar_dropout_rate";
+const PARAM_NOISE_CAMERA_PIXEL_STDDEV: &str = "noise_camera_pixel_stddev";
+const PARAM_NOISE_IMU_ACCEL_STDDEV: &str = "noise_imu_accel_stddev";
+const PARAM_NOISE_IMU_GYRO_STDDEV: &str = "noise_imu_gyro_stddev";
+const PARAM_NOISE_IMU_ACCEL_BIAS_WALK: &str = "noise_imu_accel_bias_walk";
+const PARAM_NOISE_IMU_GYRO_BIAS_WALK: &str = "noise_imu_gyro_bias_walk";
+const PARAM_NOISE_ODOM_POSITION_STDDEV: &str = "noise_odom_position_stddev";
+const PARAM_NOISE_ODOM_VELOCITY_STDDEV: &str = "noise_odom_velocity_stddev";
 const DEFAULT_CARLA_HOST: &str = "127.0.0.1";
 const DEFAULT_CARLA_PORT: u16 = 2000;
 const DEFAULT_CARLA_TIMEOUT_MILLIS: u64 = 20000;
+const DEFAULT_LIDAR_FULL_SWEEP: bool = false;
+const DEFAULT_LIDAR_POINT_LAYOUT: &str = "xyzi";
+const DEFAULT_MAP_PROJECTION: &str = "utm";
+const DEFAULT_SENSOR_QUEUE_DEPTH: usize = 2;
+const DEFAULT_SENSOR_DROP_POLICY: &str = "drop_oldest";
+const DEFAULT_SENSOR_WORKERS: usize = 4;
+const DEFAULT_LONGITUDINAL_MODE: &str = "both";
+const DEFAULT_CONTROL_TIMEOUT: f64 = 1.0;
+const DEFAULT_NOISE_SEED: u64 = 0;
 
 type ParamsMap = HashMap<String, ParameterValue>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Params {
     pub carla_host: String,
     pub carla_port: u16,
     pub carla_timeout_millis: u64,
+    pub lidar_full_sweep: bool,
+    pub lidar_point_layout: LidarPointLayout,
+    pub map_projection: MapProjection,
+    pub sensor_queue_depth: usize,
+    pub sensor_drop_policy: SensorDropPolicy,
+    pub sensor_workers: usize,
+    /// Decimation keyed by sensor type IDs or role names.
+    pub sensor_decimation: HashMap<String, DecimationParams>,
+    /// The physical maximum steering tire rotation rate in rad/s.
+    /// Unset if the rate is unlimited.
+    pub max_steering_tire_rotation_rate: Option<f64>,
+    /// The jerk limit in m/s³ used if the command does not define
+    /// one. Unset if the jerk is unlimited.
+    pub default_jerk_limit: Option<f64>,
+    pub longitudinal_mode: LongitudinalMode,
+    /// Longitudinal modes overridden per vehicle role name.
+    pub vehicle_longitudinal_modes: HashMap<String, LongitudinalMode>,
+    /// The simulation time in seconds after which the latest control
+    /// command is reported as stale.
+    pub control_timeout: f64,
+    pub noise: NoiseParams,
+}
+
+impl Params {
+    /// Looks up the decimation of a sensor. The settings for the role
+    /// name take precedence over the ones for the type ID.
+    pub fn sensor_decimation(&self, type_id: &str, role_name: &str) -> DecimationParams {
+        let by_type = self.sensor_decimation.get(type_id);
+        let by_role = self.sensor_decimation.get(role_name);
+        match (by_role, by_type) {
+            (Some(by_role), Some(by_type)) => by_role.or(by_type),
+            (Some(params), None) | (None, Some(params)) => params.clone(),
+            (None, None) => DecimationParams::default(),
+        }
+    }
+
+    /// Looks up the longitudinal mode of a vehicle.
+    pub fn longitudinal_mode(&self, role_name: &str) -> LongitudinalMode {
+        self.vehicle_longitudinal_modes
+            .get(role_name)
+            .copied()
+            .unwrap_or(self.longitudinal_mode)
+    }
+}
+
+/// The decimation of sensor outputs. Unset fields do not decimate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecimationParams {
+    /// Publishes every Nth measurement.
+    pub every_nth: Option<u32>,
+    /// The maximum publishing rate in Hz of simulation time.
+    pub max_rate: Option<f64>,
+    /// Downscales camera images by keeping every Nth pixel in both
+    /// directions.
+    pub image_scale: Option<u32>,
+}
+
+impl DecimationParams {
+    /// Fills unset fields with the ones in `other`.
+    pub fn or(&self, other: &Self) -> Self {
+        Self {
+            every_nth: self.every_nth.or(other.every_nth),
+            max_rate: self.max_rate.or(other.max_rate),
+            image_scale: self.image_scale.or(other.image_scale),
+        }
+    }
+}
+
+/// The noise injected by the bridge. Noise is disabled when the
+/// standard deviations and rates are zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseParams {
+    pub seed: u64,
+    /// The probability to drop a message.
+    pub drop_rate: f64,
+    /// The standard deviation of lidar ranges in meters.
+    pub lidar_range_stddev: f64,
+    /// The probability to drop a lidar point.
+    pub lidar_dropout_rate: f64,
+    /// The standard deviation of camera pixel values.
+    pub camera_pixel_stddev: f64,
+    /// The standard deviation of IMU accelerations in m/s².
+    pub imu_accel_stddev: f64,
+    /// The standard deviation of IMU angular velocities in rad/s.
+    pub imu_gyro_stddev: f64,
+    /// The random walk of the IMU acceleration bias in m/s²/√s.
+    pub imu_accel_bias_walk: f64,
+    /// The random walk of the IMU angular velocity bias in rad/s/√s.
+    pub imu_gyro_bias_walk: f64,
+    /// The standard deviation of odometry positions in meters.
+    pub odom_position_stddev: f64,
+    /// The standard deviation of odometry velocities in m/s.
+    pub odom_velocity_stddev: f64,
 }
 
 impl Params {
@@ -25,10 +151,48 @@ impl Params {
         let carla_host = get_carla_host(&params)?;
         let carla_port = get_carla_port(&params)?;
         let carla_timeout_millis = get_carla_timeout_millis(&params)?;
+        let lidar_full_sweep = get_lidar_full_sweep(&params)?;
+        let lidar_point_layout = get_lidar_point_layout(&params)?;
+        let map_projection = get_map_projection(&params)?;
+        let sensor_queue_depth = get_sensor_queue_depth(&params)?;
+        let sensor_drop_policy = get_sensor_drop_policy(&params)?;
+        let sensor_workers = get_sensor_workers(&params)?;
+        let sensor_decimation = get_sensor_decimation(&params)?;
+        let max_steering_tire_rotation_rate = get_max_steering_tire_rotation_rate(&params)?;
+        let default_jerk_limit = get_default_jerk_limit(&params)?;
+        let longitudinal_mode = get_longitudinal_mode(&params)?;
+        let vehicle_longitudinal_modes = get_vehicle_longitudinal_modes(&params)?;
+        let control_timeout = get_control_timeout(&params)?;
+        let noise = NoiseParams {
+            seed: get_noise_seed(&params)?,
+            drop_rate: get_noise_rate(&params, PARAM_NOISE_DROP_RATE)?,
+            lidar_range_stddev: get_noise_stddev(&params, PARAM_NOISE_LIDAR_RANGE_STDDEV)?,
+            lidar_dropout_rate: get_noise_rate(&params, PARAM_NOISE_LIDAR_DROPOUT_RATE)?,
+            camera_pixel_stddev: get_noise_stddev(&params, PARAM_NOISE_CAMERA_PIXEL_STDDEV)?,
+            imu_accel_stddev: get_noise_stddev(&params, PARAM_NOISE_IMU_ACCEL_STDDEV)?,
+            imu_gyro_stddev: get_noise_stddev(&params, PARAM_NOISE_IMU_GYRO_STDDEV)?,
+            imu_accel_bias_walk: get_noise_stddev(&params, PARAM_NOISE_IMU_ACCEL_BIAS_WALK)?,
+            imu_gyro_bias_walk: get_noise_stddev(&params, PARAM_NOISE_IMU_GYRO_BIAS_WALK)?,
+            odom_position_stddev: get_noise_stddev(&params, PARAM_NOISE_ODOM_POSITION_STDDEV)?,
+            odom_velocity_stddev: get_noise_stddev(&params, PARAM_NOISE_ODOM_VELOCITY_STDDEV)?,
+        };
         Ok(Self {
             carla_host,
             carla_port,
             carla_timeout_millis,
+            lidar_full_sweep,
+            lidar_point_layout,
+            map_projection,
+            sensor_queue_depth,
+            sensor_drop_policy,
+            sensor_workers,
+            sensor_decimation,
+            max_steering_tire_rotation_rate,
+            default_jerk_limit,
+            longitudinal_mode,
+            vehicle_longitudinal_modes,
+            control_timeout,
+            noise,
         })
     }
 }
@@ -72,9 +236,274 @@ fn get_carla_timeout_millis(params: &ParamsMap) -> Result<u64> {
     Ok(value)
 }
 
+fn get_lidar_full_sweep(params: &ParamsMap) -> Result<bool> {
+    let Some(value) = params.get(PARAM_LIDAR_FULL_SWEEP) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_LIDAR_FULL_SWEEP, PARAM_LIDAR_FULL_SWEEP);
+        return Ok(DEFAULT_LIDAR_FULL_SWEEP);
+    };
+    let value = value
+        .to_bool()
+        .ok_or_else(|| anyhow!("{PARAM_LIDAR_FULL_SWEEP} has invalid type"))?;
+    Ok(value)
+}
+
+fn get_lidar_point_layout(params: &ParamsMap) -> Result<LidarPointLayout> {
+    let Some(value) = params.get(PARAM_LIDAR_POINT_LAYOUT) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_LIDAR_POINT_LAYOUT, PARAM_LIDAR_POINT_LAYOUT);
+        return DEFAULT_LIDAR_POINT_LAYOUT.parse();
+    };
+    let value = value
+        .to_str()
+        .ok_or_else(|| anyhow!("{PARAM_LIDAR_POINT_LAYOUT} has invalid type"))?;
+    let value = value
+        .parse()
+        .with_context(|| format!("invalid {PARAM_LIDAR_POINT_LAYOUT} value '{}'", value))?;
+    Ok(value)
+}
+
+fn get_map_projection(params: &ParamsMap) -> Result<MapProjection> {
+    let Some(value) = params.get(PARAM_MAP_PROJECTION) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_MAP_PROJECTION, PARAM_MAP_PROJECTION);
+        return DEFAULT_MAP_PROJECTION.parse();
+    };
+    let value = value
+        .to_str()
+        .ok_or_else(|| anyhow!("{PARAM_MAP_PROJECTION} has invalid type"))?;
+    let value = value
+        .parse()
+        .with_context(|| format!("invalid {PARAM_MAP_PROJECTION} value '{}'", value))?;
+    Ok(value)
+}
+
+fn get_sensor_queue_depth(params: &ParamsMap) -> Result<usize> {
+    let Some(value) = params.get(PARAM_SENSOR_QUEUE_DEPTH) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_SENSOR_QUEUE_DEPTH, PARAM_SENSOR_QUEUE_DEPTH);
+        return Ok(DEFAULT_SENSOR_QUEUE_DEPTH);
+    };
+    let value = value
+        .to_i64()
+        .ok_or_else(|| anyhow!("{PARAM_SENSOR_QUEUE_DEPTH} has invalid type"))?;
+    let value: usize = value
+        .try_into()
+        .with_context(|| format!("invalid {PARAM_SENSOR_QUEUE_DEPTH} number {}", value))?;
+    ensure!(value > 0, "{PARAM_SENSOR_QUEUE_DEPTH} must be positive");
+    Ok(value)
+}
+
+fn get_sensor_drop_policy(params: &ParamsMap) -> Result<SensorDropPolicy> {
+    let Some(value) = params.get(PARAM_SENSOR_DROP_POLICY) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_SENSOR_DROP_POLICY, PARAM_SENSOR_DROP_POLICY);
+        return DEFAULT_SENSOR_DROP_POLICY.parse();
+    };
+    let value = value
+        .to_str()
+        .ok_or_else(|| anyhow!("{PARAM_SENSOR_DROP_POLICY} has invalid type"))?;
+    let value = value
+        .parse()
+        .with_context(|| format!("invalid {PARAM_SENSOR_DROP_POLICY} value '{}'", value))?;
+    Ok(value)
+}
+
+fn get_sensor_workers(params: &ParamsMap) -> Result<usize> {
+    let Some(value) = params.get(PARAM_SENSOR_WORKERS) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_SENSOR_WORKERS, PARAM_SENSOR_WORKERS);
+        return Ok(DEFAULT_SENSOR_WORKERS);
+    };
+    let value = value
+        .to_i64()
+        .ok_or_else(|| anyhow!("{PARAM_SENSOR_WORKERS} has invalid type"))?;
+    let value: usize = value
+        .try_into()
+        .with_context(|| format!("invalid {PARAM_SENSOR_WORKERS} number {}", value))?;
+    ensure!(value > 0, "{PARAM_SENSOR_WORKERS} must be positive");
+    Ok(value)
+}
+
+/// Collects parameters named `sensor_decimation.<KEY>.<FIELD>`, where
+/// the key is a sensor type ID or a role name.
+fn get_sensor_decimation(params: &ParamsMap) -> Result<HashMap<String, DecimationParams>> {
+    let mut decimation: HashMap<String, DecimationParams> = HashMap::new();
+
+    for (name, value) in params {
+        let Some(suffix) = name.strip_prefix(PARAM_SENSOR_DECIMATION) else {
+            continue;
+        };
+        let Some(suffix) = suffix.strip_prefix('.') else {
+            continue;
+        };
+        let Some((key, field)) = suffix.rsplit_once('.') else {
+            bail!("invalid parameter name '{name}'");
+        };
+        let entry = decimation.entry(key.to_string()).or_default();
+
+        match field {
+            "every_nth" | "image_scale" => {
+                let value = value
+                    .to_i64()
+                    .ok_or_else(|| anyhow!("{name} has invalid type"))?;
+                let value: u32 = value
+                    .try_into()
+                    .with_context(|| format!("invalid {name} number {}", value))?;
+                ensure!(value > 0, "{name} must be positive");
+
+                if field == "every_nth" {
+                    entry.every_nth = Some(value);
+                } else {
+                    entry.image_scale = Some(value);
+                }
+            }
+            "max_rate" => {
+                let value = value
+                    .to_f64()
+                    .ok_or_else(|| anyhow!("{name} has invalid type"))?;
+                ensure!(
+                    value.is_finite() && value > 0.0,
+                    "invalid {name} number {}",
+                    value
+                );
+                entry.max_rate = Some(value);
+            }
+            _ => bail!("unknown parameter '{name}'"),
+        }
+    }
+
+    Ok(decimation)
+}
+
+fn get_max_steering_tire_rotation_rate(params: &ParamsMap) -> Result<Option<f64>> {
+    let Some(value) = params.get(PARAM_MAX_STEERING_TIRE_ROTATION_RATE) else {
+        return Ok(None);
+    };
+    let value = value
+        .to_f64()
+        .ok_or_else(|| anyhow!("{PARAM_MAX_STEERING_TIRE_ROTATION_RATE} has invalid type"))?;
+    ensure!(
+        value.is_finite() && value > 0.0,
+        "invalid {PARAM_MAX_STEERING_TIRE_ROTATION_RATE} number {}",
+        value
+    );
+    Ok(Some(value))
+}
+
+fn get_default_jerk_limit(params: &ParamsMap) -> Result<Option<f64>> {
+    let Some(value) = params.get(PARAM_DEFAULT_JERK_LIMIT) else {
+        return Ok(None);
+    };
+    let value = value
+        .to_f64()
+        .ok_or_else(|| anyhow!("{PARAM_DEFAULT_JERK_LIMIT} has invalid type"))?;
+    ensure!(
+        value.is_finite() && value > 0.0,
+        "invalid {PARAM_DEFAULT_JERK_LIMIT} number {}",
+        value
+    );
+    Ok(Some(value))
+}
+
+fn get_longitudinal_mode(params: &ParamsMap) -> Result<LongitudinalMode> {
+    let Some(value) = params.get(PARAM_LONGITUDINAL_MODE) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_LONGITUDINAL_MODE, PARAM_LONGITUDINAL_MODE);
+        return DEFAULT_LONGITUDINAL_MODE.parse();
+    };
+    let value = value
+        .to_str()
+        .ok_or_else(|| anyhow!("{PARAM_LONGITUDINAL_MODE} has invalid type"))?;
+    let value = value
+        .parse()
+        .with_context(|| format!("invalid {PARAM_LONGITUDINAL_MODE} value '{}'", value))?;
+    Ok(value)
+}
+
+/// Collects parameters named `vehicle.<ROLE_NAME>.longitudinal_mode`.
+fn get_vehicle_longitudinal_modes(params: &ParamsMap) -> Result<HashMap<String, LongitudinalMode>> {
+    let mut modes = HashMap::new();
+
+    for (name, value) in params {
+        let Some(role_name) = name
+            .strip_prefix("vehicle.")
+            .and_then(|suffix| suffix.strip_suffix(".longitudinal_mode"))
+        else {
+            continue;
+        };
+        let value = value
+            .to_str()
+            .ok_or_else(|| anyhow!("{name} has invalid type"))?;
+        let mode = value
+            .parse()
+            .with_context(|| format!("invalid {name} value '{}'", value))?;
+        modes.insert(role_name.to_string(), mode);
+    }
+
+    Ok(modes)
+}
+
+fn get_control_timeout(params: &ParamsMap) -> Result<f64> {
+    let Some(value) = params.get(PARAM_CONTROL_TIMEOUT) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_CONTROL_TIMEOUT, PARAM_CONTROL_TIMEOUT);
+        return Ok(DEFAULT_CONTROL_TIMEOUT);
+    };
+    let value = value
+        .to_f64()
+        .ok_or_else(|| anyhow!("{PARAM_CONTROL_TIMEOUT} has invalid type"))?;
+    ensure!(
+        value.is_finite() && value > 0.0,
+        "invalid {PARAM_CONTROL_TIMEOUT} number {}",
+        value
+    );
+    Ok(value)
+}
+
+fn get_noise_seed(params: &ParamsMap) -> Result<u64> {
+    let Some(value) = params.get(PARAM_NOISE_SEED) else {
+        log_warn!(env!("CARGO_BIN_NAME"), "Using default value '{}' for parameter '{}'", DEFAULT_NOISE_SEED, PARAM_NOISE_SEED);
+        return Ok(DEFAULT_NOISE_SEED);
+    };
+    let value = value
+        .to_i64()
+        .ok_or_else(|| anyhow!("{PARAM_NOISE_SEED} has invalid type"))?;
+    let value = value
+        .try_into()
+        .with_context(|| format!("invalid {PARAM_NOISE_SEED} number {}", value))?;
+    Ok(value)
+}
+
+/// Reads a noise standard deviation, which is zero if not set.
+fn get_noise_stddev(params: &ParamsMap, name: &str) -> Result<f64> {
+    let Some(value) = params.get(name) else {
+        return Ok(0.0);
+    };
+    let value = value
+        .to_f64()
+        .ok_or_else(|| anyhow!("{name} has invalid type"))?;
+    ensure!(
+        value.is_finite() && value >= 0.0,
+        "invalid {name} number {}",
+        value
+    );
+    Ok(value)
+}
+
+/// Reads a noise probability, which is zero if not set.
+fn get_noise_rate(params: &ParamsMap, name: &str) -> Result<f64> {
+    let Some(value) = params.get(name) else {
+        return Ok(0.0);
+    };
+    let value = value
+        .to_f64()
+        .ok_or_else(|| anyhow!("{name} has invalid type"))?;
+    ensure!(
+        (0.0..=1.0).contains(&value),
+        "invalid {name} number {}",
+        value
+    );
+    Ok(value)
+}
+
 pub trait ParameterValueExt {
     fn to_str(&self) -> Option<&str>;
     fn to_i64(&self) -> Option<i64>;
+    fn to_f64(&self) -> Option<f64>;
+    fn to_bool(&self) -> Option<bool>;
 }
 
 impl ParameterValueExt for ParameterValue {
@@ -93,4 +522,20 @@ impl ParameterValueExt for ParameterValue {
             None
         }
     }
+
+    fn to_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Double(val) => Some(val),
+            Self::Integer(val) => Some(val as f64),
+            _ => None,
+        }
+    }
+
+    fn to_bool(&self) -> Option<bool> {
+        if let Self::Bool(val) = *self {
+            Some(val)
+        } else {
+            None
+        }
+    }
 }
@@ -17,6 +17,28 @@ pub fn best_effort() -> QosProfile {
     }
 }
 
+pub fn reliable() -> QosProfile {
+    QosProfile {
+        history: HistoryPolicy::KeepLast,
+        depth: 100,
+        reliability: ReliabilityPolicy::Reliable,
+        durability: DurabilityPolicy::Volatile,
+        ..QosProfile::default()
+    }
+}
+
+/// The profile of tf2's static transform broadcaster, which keeps
+/// only the last message for late subscribers.
+pub fn static_tf() -> QosProfile {
+    QosProfile {
+        history: HistoryPolicy::KeepLast,
+        depth: 1,
+        reliability: ReliabilityPolicy::Reliable,
+        durability: DurabilityPolicy::TransientLocal,
+        ..QosProfile::default()
+    }
+}
+
 pub fn latched() -> QosProfile {
     QosProfile {
         history: HistoryPolicy::KeepAll,
@@ -1,6 +1,10 @@
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use carla::client::Timestamp;
+use r2r::{builtin_interfaces::msg::Time as RosTime, Clock};
 
 #[derive(Debug)]
 pub struct TimeBuffer {
@@ -46,6 +50,51 @@ impl Default for TimeBuffer {
     }
 }
 
+/// Maps simulation timestamps onto the ROS time base.
+///
+/// The simulation time is anchored to the ROS time once, and later
+/// stamps are offset by the elapsed simulation time from the
+/// anchor. Messages from the same simulation frame thus get identical
+/// stamps regardless of when they are processed.
+#[derive(Debug, Clone, Default)]
+pub struct SimClock {
+    anchor: Arc<Mutex<Option<Anchor>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    sim_secs: f64,
+    ros_time: Duration,
+}
+
+impl SimClock {
+    /// Anchors the simulation time to the ROS time if it is not
+    /// anchored yet.
+    pub fn anchor(&self, sim_secs: f64, ros_time: Duration) {
+        let mut anchor = self.anchor.lock().unwrap();
+        anchor.get_or_insert(Anchor { sim_secs, ros_time });
+    }
+
+    /// Anchors the simulation time to the ROS time again, for example,
+    /// when the simulation time restarts after the world is reloaded.
+    pub fn reanchor(&self, sim_secs: f64, ros_time: Duration) {
+        let mut anchor = self.anchor.lock().unwrap();
+        *anchor = Some(Anchor { sim_secs, ros_time });
+    }
+
+    /// Converts a simulation timestamp in seconds to the ROS time.
+    ///
+    /// It returns `None` if the clock is not anchored yet.
+    pub fn stamp(&self, sim_secs: f64) -> Option<RosTime> {
+        let Anchor {
+            sim_secs: anchor_secs,
+            ros_time,
+        } = (*self.anchor.lock().unwrap())?;
+        let elapsed = Duration::from_secs_f64((sim_secs - anchor_secs).max(0.0));
+        Some(Clock::to_builtin_time(&(ros_time + elapsed)))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimeDelta {
     pub frame: usize,
@@ -73,10 +73,29 @@ pub enum PointFieldType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SensorType {
     CameraRgb,
+    CameraDepth,
+    CameraSemanticSegmentation,
+    CameraInstanceSegmentation,
     LidarRayCast,
     LidarRayCastSemantic,
+    Radar,
+    Gnss,
     Imu,
     Collision,
+    LaneInvasion,
+    Obstacle,
+}
+
+impl SensorType {
+    pub fn is_camera(&self) -> bool {
+        matches!(
+            self,
+            Self::CameraRgb
+                | Self::CameraDepth
+                | Self::CameraSemanticSegmentation
+                | Self::CameraInstanceSegmentation
+        )
+    }
 }
 
 impl FromStr for SensorType {
@@ -85,15 +104,368 @@ impl FromStr for SensorType {
     fn from_str(text: &str) -> Result<Self, Self::Err> {
         Ok(match text {
             "sensor.camera.rgb" => Self::CameraRgb,
+            "sensor.camera.depth" => Self::CameraDepth,
+            "sensor.camera.semantic_segmentation" => Self::CameraSemanticSegmentation,
+            "sensor.camera.instance_segmentation" => Self::CameraInstanceSegmentation,
             "sensor.lidar.ray_cast" => Self::LidarRayCast,
             "sensor.lidar.ray_cast_semantic" => Self::LidarRayCastSemantic,
+            "sensor.other.radar" => Self::Radar,
+            "sensor.other.gnss" => Self::Gnss,
             "sensor.other.imu" => Self::Imu,
             "sensor.other.collision" => Self::Collision,
+            "sensor.other.lane_invasion" => Self::LaneInvasion,
+            "sensor.other.obstacle" => Self::Obstacle,
             _ => bail!("Unsupported type '{}'", text),
         })
     }
 }
 
+/// The point cloud layout of lidar sensors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LidarPointLayout {
+    /// Position and intensity fields.
+    Xyzi,
+    /// Autoware's PointXYZIRCAEDT fields.
+    Xyzircaedt,
+}
+
+impl FromStr for LidarPointLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text {
+            "xyzi" => Self::Xyzi,
+            "xyzircaedt" => Self::Xyzircaedt,
+            _ => bail!("Unsupported lidar point layout '{}'", text),
+        })
+    }
+}
+
+/// The projection of the Lanelet2 map served by the map service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapProjection {
+    /// The fixed UTM zone 32.
+    Utm,
+    /// A transverse Mercator projection around the origin of the
+    /// OpenDRIVE geo-reference, which agrees with GNSS sensors.
+    GeoReference,
+}
+
+impl FromStr for MapProjection {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text {
+            "utm" => Self::Utm,
+            "geo_reference" => Self::GeoReference,
+            _ => bail!("Unsupported map projection '{}'", text),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum DiagnosticLevel {
+    OK = 0,
+    WARN = 1,
+    ERROR = 2,
+    STALE = 3,
+}
+
+/// The policy to drop sensor data when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorDropPolicy {
+    /// Keeps the queued data and drops the incoming one.
+    DropNewest,
+    /// Drops the oldest queued data to make room for the incoming one.
+    DropOldest,
+}
+
+impl FromStr for SensorDropPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text {
+            "drop_newest" => Self::DropNewest,
+            "drop_oldest" => Self::DropOldest,
+            _ => bail!("Unsupported sensor drop policy '{}'", text),
+        })
+    }
+}
+
+/// The longitudinal target tracked by Ackermann control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongitudinalMode {
+    /// Tracks the target velocity and ignores the acceleration.
+    Velocity,
+    /// Tracks the target acceleration and ignores the velocity.
+    Acceleration,
+    /// Tracks the target velocity with the acceleration as the limit.
+    Both,
+}
+
+impl FromStr for LongitudinalMode {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text {
+            "velocity" => Self::Velocity,
+            "acceleration" => Self::Acceleration,
+            "both" => Self::Both,
+            _ => bail!("Unsupported longitudinal mode '{}'", text),
+        })
+    }
+}
+
+/// The return type of lidar points defined by Autoware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum LidarReturnType {
+    INVALID = 0,
+    SINGLE_STRONGEST = 1,
+    SINGLE_LAST = 2,
+    DUAL_STRONGEST_FIRST = 3,
+    DUAL_STRONGEST_LAST = 4,
+    DUAL_WEAK_FIRST = 5,
+    DUAL_WEAK_LAST = 6,
+    DUAL_ONLY = 7,
+}
+
+/// The gear values shared by Autoware's `GearCommand` and
+/// `GearReport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum GearType {
+    NONE = 0,
+    NEUTRAL = 1,
+    DRIVE = 2,
+    DRIVE_2 = 3,
+    DRIVE_3 = 4,
+    DRIVE_4 = 5,
+    DRIVE_5 = 6,
+    DRIVE_6 = 7,
+    DRIVE_7 = 8,
+    DRIVE_8 = 9,
+    DRIVE_9 = 10,
+    DRIVE_10 = 11,
+    DRIVE_11 = 12,
+    DRIVE_12 = 13,
+    DRIVE_13 = 14,
+    DRIVE_14 = 15,
+    DRIVE_15 = 16,
+    DRIVE_16 = 17,
+    DRIVE_17 = 18,
+    DRIVE_18 = 19,
+    REVERSE = 20,
+    REVERSE_2 = 21,
+    PARK = 22,
+    LOW = 23,
+    LOW_2 = 24,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum ControlModeType {
+    NO_COMMAND = 0,
+    AUTONOMOUS = 1,
+    AUTONOMOUS_STEER_ONLY = 2,
+    AUTONOMOUS_VELOCITY_ONLY = 3,
+    MANUAL = 4,
+    DISENGAGED = 5,
+    NOT_READY = 6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum TurnIndicatorsReportType {
+    DISABLE = 1,
+    ENABLE_LEFT = 2,
+    ENABLE_RIGHT = 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum HazardLightsReportType {
+    DISABLE = 1,
+    ENABLE = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(i8)]
+pub enum NavSatStatusType {
+    NO_FIX = -1,
+    FIX = 0,
+    SBAS_FIX = 1,
+    GBAS_FIX = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u16)]
+pub enum NavSatServiceType {
+    GPS = 1,
+    GLONASS = 2,
+    COMPASS = 4,
+    GALILEO = 8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum NavSatCovarianceType {
+    UNKNOWN = 0,
+    APPROXIMATED = 1,
+    DIAGONAL_KNOWN = 2,
+    KNOWN = 3,
+}
+
+/// Object tags of semantic segmentation cameras and semantic lidars
+/// in Carla 0.9.14, the version targeted by the `carla` crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+#[allow(non_camel_case_types)]
+#[repr(u8)]
+pub enum SemanticTag {
+    UNLABELED = 0,
+    ROAD = 1,
+    SIDEWALK = 2,
+    BUILDING = 3,
+    WALL = 4,
+    FENCE = 5,
+    POLE = 6,
+    TRAFFIC_LIGHT = 7,
+    TRAFFIC_SIGN = 8,
+    VEGETATION = 9,
+    TERRAIN = 10,
+    SKY = 11,
+    PEDESTRIAN = 12,
+    RIDER = 13,
+    CAR = 14,
+    TRUCK = 15,
+    BUS = 16,
+    TRAIN = 17,
+    MOTORCYCLE = 18,
+    BICYCLE = 19,
+    STATIC = 20,
+    DYNAMIC = 21,
+    OTHER = 22,
+    WATER = 23,
+    ROAD_LINE = 24,
+    GROUND = 25,
+    BRIDGE = 26,
+    RAIL_TRACK = 27,
+    GUARD_RAIL = 28,
+}
+
+impl SemanticTag {
+    pub const ALL: [Self; 29] = [
+        Self::UNLABELED,
+        Self::ROAD,
+        Self::SIDEWALK,
+        Self::BUILDING,
+        Self::WALL,
+        Self::FENCE,
+        Self::POLE,
+        Self::TRAFFIC_LIGHT,
+        Self::TRAFFIC_SIGN,
+        Self::VEGETATION,
+        Self::TERRAIN,
+        Self::SKY,
+        Self::PEDESTRIAN,
+        Self::RIDER,
+        Self::CAR,
+        Self::TRUCK,
+        Self::BUS,
+        Self::TRAIN,
+        Self::MOTORCYCLE,
+        Self::BICYCLE,
+        Self::STATIC,
+        Self::DYNAMIC,
+        Self::OTHER,
+        Self::WATER,
+        Self::ROAD_LINE,
+        Self::GROUND,
+        Self::BRIDGE,
+        Self::RAIL_TRACK,
+        Self::GUARD_RAIL,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UNLABELED => "Unlabeled",
+            Self::ROAD => "Road",
+            Self::SIDEWALK => "SideWalk",
+            Self::BUILDING => "Building",
+            Self::WALL => "Wall",
+            Self::FENCE => "Fence",
+            Self::POLE => "Pole",
+            Self::TRAFFIC_LIGHT => "TrafficLight",
+            Self::TRAFFIC_SIGN => "TrafficSign",
+            Self::VEGETATION => "Vegetation",
+            Self::TERRAIN => "Terrain",
+            Self::SKY => "Sky",
+            Self::PEDESTRIAN => "Pedestrian",
+            Self::RIDER => "Rider",
+            Self::CAR => "Car",
+            Self::TRUCK => "Truck",
+            Self::BUS => "Bus",
+            Self::TRAIN => "Train",
+            Self::MOTORCYCLE => "Motorcycle",
+            Self::BICYCLE => "Bicycle",
+            Self::STATIC => "Static",
+            Self::DYNAMIC => "Dynamic",
+            Self::OTHER => "Other",
+            Self::WATER => "Water",
+            Self::ROAD_LINE => "RoadLine",
+            Self::GROUND => "Ground",
+            Self::BRIDGE => "Bridge",
+            Self::RAIL_TRACK => "RailTrack",
+            Self::GUARD_RAIL => "GuardRail",
+        }
+    }
+
+    /// Gets the color in Carla's CityScapes palette in (R, G, B) order.
+    pub fn cityscapes_color(&self) -> [u8; 3] {
+        match self {
+            Self::UNLABELED => [0, 0, 0],
+            Self::ROAD => [128, 64, 128],
+            Self::SIDEWALK => [244, 35, 232],
+            Self::BUILDING => [70, 70, 70],
+            Self::WALL => [102, 102, 156],
+            Self::FENCE => [190, 153, 153],
+            Self::POLE => [153, 153, 153],
+            Self::TRAFFIC_LIGHT => [250, 170, 30],
+            Self::TRAFFIC_SIGN => [220, 220, 0],
+            Self::VEGETATION => [107, 142, 35],
+            Self::TERRAIN => [152, 251, 152],
+            Self::SKY => [70, 130, 180],
+            Self::PEDESTRIAN => [220, 20, 60],
+            Self::RIDER => [255, 0, 0],
+            Self::CAR => [0, 0, 142],
+            Self::TRUCK => [0, 0, 70],
+            Self::BUS => [0, 60, 100],
+            Self::TRAIN => [0, 80, 100],
+            Self::MOTORCYCLE => [0, 0, 230],
+            Self::BICYCLE => [119, 11, 32],
+            Self::STATIC => [110, 190, 160],
+            Self::DYNAMIC => [170, 120, 50],
+            Self::OTHER => [55, 90, 80],
+            Self::WATER => [45, 60, 150],
+            Self::ROAD_LINE => [157, 234, 50],
+            Self::GROUND => [81, 0, 81],
+            Self::BRIDGE => [150, 100, 100],
+            Self::RAIL_TRACK => [230, 150, 140],
+            Self::GUARD_RAIL => [180, 165, 180],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[allow(non_camel_case_types)]
 #[repr(u8)]
@@ -1,16 +1,18 @@
+use crate::coord;
 use carla::{client::ActorBase, geom::BoundingBox};
 use nalgebra as na;
-use ndarray::Array2;
+use ndarray::{Array1, Array2};
 use r2r::{
     builtin_interfaces::msg::Time,
     geometry_msgs::msg::{
         Accel, AccelWithCovariance, AccelWithCovarianceStamped, Point, Point32, Pose,
-        PoseWithCovariance, Quaternion, Twist, TwistWithCovariance, Vector3,
+        PoseWithCovariance, Quaternion, Transform, Twist, TwistWithCovariance, Vector3,
     },
     moveit_msgs::msg::OrientedBoundingBox,
     nav_msgs::msg::Odometry,
     std_msgs::msg::Header,
 };
+use std::str::FromStr;
 
 pub struct ActorPhysics {
     pub transform: na::Isometry3<f32>,
@@ -22,24 +24,34 @@ pub struct ActorPhysics {
 }
 
 pub trait ActorExt: ActorBase {
-    fn create_physics_msg(&self, time: Time) -> ActorPhysics {
-        let transform = self.transform();
-        let velocity = self.velocity();
-        let angular_velocity = self.angular_velocity();
-        let accel = self.acceleration();
+    /// Creates odometry and acceleration messages of the actor.
+    ///
+    /// The pose and acceleration are expressed in the map frame, while
+    /// the twist is expressed in the actor frame named `frame_id`.
+    fn create_physics_msg(&self, time: Time, frame_id: &str) -> ActorPhysics {
+        let transform = coord::isometry(&self.transform());
+        let velocity = coord::vector(&self.velocity());
+        let angular_velocity = coord::angular_velocity_degrees(&self.angular_velocity());
+        let accel = coord::vector(&self.acceleration());
 
         let header = Header {
             stamp: time,
-            frame_id: "".to_string(),
+            frame_id: "map".to_string(),
         };
         let odom_msg = Odometry {
             header: header.clone(),
-            child_frame_id: "".to_string(),
+            child_frame_id: frame_id.to_string(),
             pose: transform.to_ros_type(),
             twist: TwistWithCovariance {
                 twist: Twist {
-                    linear: velocity.to_ros_type(),
-                    angular: angular_velocity.to_ros_type(),
+                    linear: transform
+                        .rotation
+                        .inverse_transform_vector(&velocity)
+                        .to_ros_type(),
+                    angular: transform
+                        .rotation
+                        .inverse_transform_vector(&angular_velocity)
+                        .to_ros_type(),
                 },
                 covariance: identity_matrix(6).into_raw_vec(),
             },
@@ -69,6 +81,19 @@ pub trait ActorExt: ActorBase {
             accel_msg,
         }
     }
+
+    /// Finds the attribute with the given ID and parses its value.
+    fn parse_attribute<V>(&self, id: &str) -> Option<V>
+    where
+        V: FromStr,
+    {
+        self.attributes()
+            .iter()
+            .find(|attr| attr.id() == id)?
+            .value_string()
+            .parse()
+            .ok()
+    }
 }
 
 impl<T> ActorExt for T where T: ActorBase {}
@@ -107,6 +132,27 @@ impl ToRosType<Pose> for na::Isometry3<f32> {
     }
 }
 
+impl ToRosType<Transform> for na::Isometry3<f64> {
+    fn to_ros_type(&self) -> Transform {
+        let na::Isometry3 {
+            rotation,
+            translation,
+        } = self;
+
+        Transform {
+            translation: translation.vector.to_ros_type(),
+            rotation: rotation.to_ros_type(),
+        }
+    }
+}
+
+impl ToRosType<Transform> for na::Isometry3<f32> {
+    fn to_ros_type(&self) -> Transform {
+        let val: na::Isometry3<f64> = na::convert_ref(self);
+        val.to_ros_type()
+    }
+}
+
 impl ToRosType<PoseWithCovariance> for na::Isometry3<f64> {
     fn to_ros_type(&self) -> PoseWithCovariance {
         PoseWithCovariance {
@@ -171,7 +217,7 @@ impl ToRosType<Quaternion> for na::UnitQuaternion<f32> {
 impl ToRosType<OrientedBoundingBox> for BoundingBox<f32> {
     fn to_ros_type(&self) -> OrientedBoundingBox {
         OrientedBoundingBox {
-            pose: self.transform.to_ros_type(),
+            pose: coord::isometry(&self.transform).to_ros_type(),
             extents: self.extent.to_ros_type(),
         }
     }
@@ -180,3 +226,7 @@ impl ToRosType<OrientedBoundingBox> for BoundingBox<f32> {
 pub fn identity_matrix(size: usize) -> Array2<f64> {
     Array2::from_diag_elem(size, 1.0)
 }
+
+pub fn diagonal_matrix(diag: &[f64]) -> Array2<f64> {
+    Array2::from_diag(&Array1::from_vec(diag.to_vec()))
+}
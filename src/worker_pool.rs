@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use tokio::{
+    sync::Semaphore,
+    task::{spawn_blocking, JoinError},
+};
+
+/// Runs blocking jobs with bounded concurrency.
+#[derive(Debug, Clone)]
+pub struct WorkerPool {
+    semaphore: Arc<Semaphore>,
+}
+
+impl WorkerPool {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(workers)),
+        }
+    }
+
+    /// Waits for an idle worker and runs the job on it. It fails if
+    /// the job panics.
+    pub async fn run<F, T>(&self, job: F) -> Result<T, JoinError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let _permit = self.semaphore.acquire().await.unwrap();
+        spawn_blocking(job).await
+    }
+}